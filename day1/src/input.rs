@@ -0,0 +1,94 @@
+use std::io::{BufRead, ErrorKind};
+use std::str::FromStr;
+
+// Lines that are not readings and can be left out instead of being errors
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ParseOptions {
+    pub skip_blank_lines: bool,
+    // Lines starting with `#`
+    pub skip_comments: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum InputError {
+    // Lines start at 1, the content is the line as read
+    InvalidReading { line: usize, content: String },
+    Io(ErrorKind),
+}
+
+impl From<std::io::Error> for InputError {
+    fn from(error: std::io::Error) -> Self {
+        InputError::Io(error.kind())
+    }
+}
+
+// The reading of one line, None if the line is skipped. Whitespace around the reading doesn't matter,
+// which includes the carriage return of Windows line endings.
+pub fn parse_reading(line: &str, line_number: usize, options: &ParseOptions) -> Result<Option<i32>, InputError> {
+    let trimmed = line.trim();
+
+    if (options.skip_blank_lines && trimmed.is_empty()) || (options.skip_comments && trimmed.starts_with('#')) {
+        return Ok(None);
+    }
+
+    i32::from_str(trimmed)
+        .map(Some)
+        .map_err(|_| InputError::InvalidReading { line: line_number, content: line.to_string() })
+}
+
+// Each reading with the number of its line, which skipped lines still count in
+pub fn parse_readings<R: BufRead>(input: R, options: &ParseOptions) -> Result<Vec<(usize, i32)>, InputError> {
+    let mut readings = Vec::new();
+
+    for (idx, line) in input.lines().enumerate() {
+        if let Some(reading) = parse_reading(&line?, idx + 1, options)? {
+            readings.push((idx + 1, reading));
+        }
+    }
+
+    Ok(readings)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::{parse_readings, InputError, ParseOptions};
+
+    const SKIP_ALL: ParseOptions = ParseOptions { skip_blank_lines: true, skip_comments: true };
+
+    macro_rules! parse_test {
+        ($name:ident, $input:literal, $options:expr => $result:expr) => {
+            #[test]
+            fn $name(){
+                let readings = parse_readings(Cursor::new($input), &$options)
+                    .map(|readings| readings.into_iter().map(|(_, reading)| reading).collect::<Vec<_>>());
+                assert_eq!(readings, $result);
+            }
+        };
+    }
+
+    parse_test!(plain,                      "199\n200\n208", ParseOptions::default() => Ok(vec![199, 200, 208]));
+    parse_test!(trailing_newline,           "199\n200\n", ParseOptions::default() => Ok(vec![199, 200]));
+    parse_test!(windows_line_endings,       "199\r\n200\r\n", ParseOptions::default() => Ok(vec![199, 200]));
+    parse_test!(spaces_around,              "  199 \n\t200\n", ParseOptions::default() => Ok(vec![199, 200]));
+    parse_test!(negative_depth,             "-3\n4\n", ParseOptions::default() => Ok(vec![-3, 4]));
+
+    parse_test!(blank_line_is_an_error,     "199\n\n200\n", ParseOptions::default()
+        => Err(InputError::InvalidReading { line: 2, content: "".to_string() }));
+    parse_test!(stray_character,            "199\n20O\n", ParseOptions::default()
+        => Err(InputError::InvalidReading { line: 2, content: "20O".to_string() }));
+    parse_test!(comment_is_an_error,        "# depths\n199\n", ParseOptions::default()
+        => Err(InputError::InvalidReading { line: 1, content: "# depths".to_string() }));
+
+    parse_test!(skip_blank_lines,           "199\n\n  \n200\n", SKIP_ALL => Ok(vec![199, 200]));
+    parse_test!(skip_comments,              "# depths\n199\n  # more\n200\n", SKIP_ALL => Ok(vec![199, 200]));
+    parse_test!(line_numbers_count_skipped, "# depths\n\n199\nabc\n", SKIP_ALL
+        => Err(InputError::InvalidReading { line: 4, content: "abc".to_string() }));
+
+    #[test]
+    fn numbered_readings_keep_their_lines(){
+        let readings = parse_readings(Cursor::new("# depths\n199\n\n  \n200\n# more\n208\n"), &SKIP_ALL);
+        assert_eq!(readings, Ok(vec![(2, 199), (5, 200), (7, 208)]));
+    }
+}
@@ -1,56 +1,192 @@
 use std::env::args;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{stdin, stdout, BufRead, BufReader};
 use std::str::FromStr;
 
+use input::{parse_readings, ParseOptions};
+use stats::{Report, Statistic};
+use stream::IncreaseCounter;
+
+mod input;
+mod stats;
+mod stream;
+
+const DEFAULT_AVERAGE_WINDOW: usize = 3;
+
+// Any form also accepts `--skip-blank` and `--skip-comments`
+const USAGE: &str = "USAGE: day1 <input file> [--window <size>] \
+| day1 <input file> --stats all|<statistic>,<statistic>... [--average-window <size>] [--json] \
+| day1 --stream [<input file>|-] [--window <size>] [--progress <count>]";
+
 fn main() {
-    let input_filename = args().nth(1).expect("USAGE: day1 <input file>");
-    let file = File::open(&input_filename).unwrap_or_else(|_| panic!("Can't open file {}", input_filename));
+    let arguments = args().skip(1).collect::<Vec<_>>();
+
+    let mut input_filename = None;
+    let mut window = None;
+    let mut streaming = false;
+    let mut progress_every = None;
+    let mut statistics = None;
+    let mut average_window = DEFAULT_AVERAGE_WINDOW;
+    let mut export_json = false;
+    let mut parse_options = ParseOptions::default();
+
+    let mut arguments_iter = arguments.iter();
+    while let Some(argument) = arguments_iter.next() {
+        if argument == "--window" {
+            window = Some(arguments_iter.next().and_then(|n| usize::from_str(n).ok()).filter(|n| *n > 0).expect(USAGE));
+        } else if argument == "--stream" {
+            streaming = true;
+        } else if argument == "--progress" {
+            progress_every = Some(arguments_iter.next().and_then(|n| usize::from_str(n).ok()).filter(|n| *n > 0).expect(USAGE));
+        } else if argument == "--stats" {
+            statistics = Some(parse_statistics(arguments_iter.next().expect(USAGE)));
+        } else if argument == "--average-window" {
+            average_window = arguments_iter.next().and_then(|n| usize::from_str(n).ok()).filter(|n| *n > 0).expect(USAGE);
+        } else if argument == "--json" {
+            export_json = true;
+        } else if argument == "--skip-blank" {
+            parse_options.skip_blank_lines = true;
+        } else if argument == "--skip-comments" {
+            parse_options.skip_comments = true;
+        } else {
+            input_filename = Some(argument);
+        }
+    }
+
+    if streaming {
+        stream_readings(input_filename.map(String::as_str), window, &parse_options, progress_every);
+        return;
+    }
+
+    let input_filename = input_filename.expect(USAGE);
+    let file = File::open(input_filename).unwrap_or_else(|_| panic!("Can't open file {}", input_filename));
     let file = BufReader::new(file);
 
-    let lines = file
-        .lines()
-        .map(Result::unwrap)
-        .map(|l| i32::from_str(&l))
-        .map(Result::unwrap)
-        .collect::<Vec<i32>>();
+    let (line_numbers, lines): (Vec<_>, Vec<_>) = parse_readings(file, &parse_options)
+        .unwrap_or_else(|e| panic!("Invalid input: {:?}", e))
+        .into_iter()
+        .unzip();
+
+    if let Some(statistics) = statistics {
+        let report = Report::new(&lines, &line_numbers, average_window);
+
+        if export_json {
+            println!("{}", report.to_json(&statistics));
+        } else {
+            print!("{}", report.to_text(&statistics));
+        }
+        return;
+    }
+
+    if let Some(window) = window {
+        println!("Increase counts with window of {}: {}", window, count_increases(&lines, window));
+        return;
+    }
 
     part1(&lines);
     part2(&lines);
 }
 
-fn part1(lines: &[i32]){
-    let (_, nb_increased_readings) = lines
-        .iter()
-        .fold((None, 0), |(previous_depth, nb_increased), depth|{
-            if let Some(prev_depth) = previous_depth {
-                if depth > prev_depth {
-                    return (Some(depth), nb_increased + 1);
-                }
-            }
+// Either `all` or names of statistics separated by commas
+fn parse_statistics(names: &str) -> Vec<Statistic> {
+    if names == "all" {
+        return Statistic::ALL.to_vec();
+    }
+
+    names
+        .split(',')
+        .map(|name| Statistic::from_str(name).unwrap_or_else(|name| panic!("Unknown statistic {}, {}", name, USAGE)))
+        .collect()
+}
+
+// Analyses the readings as they come, from the standard input if there is no file or the file is `-`
+fn stream_readings(input_filename: Option<&str>, window: Option<usize>, parse_options: &ParseOptions, progress_every: Option<usize>) {
+    let mut counters = match window {
+        Some(window) => vec![IncreaseCounter::new(window)],
+        None => vec![IncreaseCounter::new(1), IncreaseCounter::new(3)],
+    };
 
-            (Some(depth), nb_increased)
-        });
+    let input: Box<dyn BufRead> = match input_filename {
+        None | Some("-") => Box::new(stdin().lock()),
+        Some(filename) => Box::new(BufReader::new(
+            File::open(filename).unwrap_or_else(|_| panic!("Can't open file {}", filename))
+        )),
+    };
 
-    println!("Nb of increasing depth: {}", nb_increased_readings);
+    let reading_count = stream::analyse(input, &mut counters, parse_options, progress_every, &mut stdout())
+        .unwrap_or_else(|e| panic!("Invalid input: {:?}", e));
+    println!("After {} readings: {}", reading_count, stream::summary(&counters));
+}
+
+// Number of times the sum of `window` consecutive readings is larger than the sum of the previous ones.
+// Both sums share all their readings but the first of the previous window and the last of the current
+// one, so only these two have to be compared.
+fn count_increases(readings: &[i32], window: usize) -> usize {
+    assert!(window > 0, "The window needs at least one reading");
+
+    readings
+        .iter()
+        .zip(readings.iter().skip(window))
+        .filter(|(leaving, entering)| entering > leaving)
+        .count()
+}
+
+fn part1(lines: &[i32]){
+    println!("Nb of increasing depth: {}", count_increases(lines, 1));
 }
 
 fn part2(lines: &[i32]){
-    let (_, nb_increased_readings) = lines.iter()
-        .zip(lines.iter().skip(1))
-        .zip(lines.iter().skip(2))
-        .map(|((a, b), c)| (*a, *b, *c))
-        .fold((None, 0), |(prev_sum, nb_increased), (line, follow1, follow2)| {
-            let current_sum = line + follow1 + follow2;
-
-            if let Some(prev_sum) = prev_sum {
-                if current_sum > prev_sum {
-                    return (Some(current_sum), nb_increased + 1);
-                }
+    println!("Increase counts with window of 3: {}", count_increases(lines, 3));
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::str::FromStr;
+
+    use super::count_increases;
+
+    fn get_readings(filename: &str) -> Vec<i32> {
+        fs::read_to_string(filename)
+            .unwrap()
+            .lines()
+            .map(|l| i32::from_str(l).unwrap())
+            .collect()
+    }
+
+    // Sums every window, like the first version of part 2
+    fn count_increases_by_sums(readings: &[i32], window: usize) -> usize {
+        let sums = readings.windows(window).map(|w| w.iter().sum::<i32>()).collect::<Vec<_>>();
+        sums.windows(2).filter(|pair| pair[1] > pair[0]).count()
+    }
+
+    macro_rules! increases_test {
+        ($name:ident, $filename:literal, window $window:literal -> $count:literal) => {
+            #[test]
+            fn $name(){
+                let readings = get_readings($filename);
+                assert_eq!(count_increases(&readings, $window), $count);
             }
+        };
+    }
+
+    increases_test!(example_part1,  "../files/day1/example.txt", window 1 -> 7);
+    increases_test!(example_part2,  "../files/day1/example.txt", window 3 -> 5);
+    increases_test!(input_part1,    "../files/day1/input1.txt", window 1 -> 1696);
+    increases_test!(input_part2,    "../files/day1/input1.txt", window 3 -> 1737);
 
-            (Some(current_sum), nb_increased)
-        });
+    #[test]
+    fn same_as_summing_windows(){
+        let readings = get_readings("../files/day1/input1.txt");
 
-    println!("Increase counts with window of 3: {}", nb_increased_readings);
-}
\ No newline at end of file
+        for window in 1..=10 {
+            assert_eq!(count_increases(&readings, window), count_increases_by_sums(&readings, window), "Window of {}", window);
+        }
+    }
+
+    #[test]
+    fn window_larger_than_readings(){
+        assert_eq!(count_increases(&[1, 2, 3], 3), 0);
+        assert_eq!(count_increases(&[], 1), 0);
+    }
+}
@@ -0,0 +1,294 @@
+use std::str::FromStr;
+
+// What can be asked for in the report
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Statistic {
+    Decreases,
+    Plateaus,
+    LongestRun,
+    LargestJump,
+    MovingAverage,
+    Extremes,
+}
+
+impl Statistic {
+    pub const ALL: [Statistic; 6] = [
+        Statistic::Decreases,
+        Statistic::Plateaus,
+        Statistic::LongestRun,
+        Statistic::LargestJump,
+        Statistic::MovingAverage,
+        Statistic::Extremes,
+    ];
+}
+
+impl FromStr for Statistic {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "decreases" => Statistic::Decreases,
+            "plateaus" => Statistic::Plateaus,
+            "run" => Statistic::LongestRun,
+            "jump" => Statistic::LargestJump,
+            "average" => Statistic::MovingAverage,
+            "extremes" => Statistic::Extremes,
+            _ => return Err(s.to_string()),
+        })
+    }
+}
+
+// Positions are the line numbers of the readings in the input, starting at 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Depth {
+    pub depth: i32,
+    pub position: usize,
+}
+
+// Readings that keep getting deeper
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Run {
+    pub start: usize,
+    pub length: usize,
+}
+
+// Largest change between two readings in a row, up or down
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Jump {
+    pub from: Depth,
+    pub to: Depth,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub decreases: usize,
+    // Readings equal to the previous one
+    pub plateaus: usize,
+    pub longest_run: Option<Run>,
+    pub largest_jump: Option<Jump>,
+    pub average_window: usize,
+    pub moving_average: Vec<f64>,
+    // First reading with the smallest or largest depth
+    pub min: Option<Depth>,
+    pub max: Option<Depth>,
+}
+
+impl Report {
+    // `line_numbers` has the line of each reading, lines left out of the readings keep their numbers
+    pub fn new(readings: &[i32], line_numbers: &[usize], average_window: usize) -> Self {
+        assert!(average_window > 0, "The window needs at least one reading");
+        assert_eq!(readings.len(), line_numbers.len(), "Each reading needs a line number");
+
+        let depth = |idx: usize| Depth { depth: readings[idx], position: line_numbers[idx] };
+        let pairs = || readings.windows(2).enumerate();
+
+        let decreases = pairs().filter(|(_, pair)| pair[1] < pair[0]).count();
+        let plateaus = pairs().filter(|(_, pair)| pair[1] == pair[0]).count();
+
+        let largest_jump = pairs()
+            .max_by_key(|(idx, pair)| (pair[0].abs_diff(pair[1]), std::cmp::Reverse(*idx)))
+            .map(|(idx, _)| Jump { from: depth(idx), to: depth(idx + 1) });
+
+        // Any reading is a run on its own
+        let mut current_run = Run { start: line_numbers.first().copied().unwrap_or(1), length: 1 };
+        let mut longest_run = Some(current_run).filter(|_| !readings.is_empty());
+        for (idx, pair) in pairs() {
+            if pair[1] > pair[0] {
+                current_run.length += 1;
+            } else {
+                current_run = Run { start: line_numbers[idx + 1], length: 1 };
+            }
+
+            if longest_run.is_some_and(|run| current_run.length > run.length) {
+                longest_run = Some(current_run);
+            }
+        }
+
+        let moving_average = readings
+            .windows(average_window)
+            .map(|window| window.iter().map(|depth| *depth as i64).sum::<i64>() as f64 / average_window as f64)
+            .collect();
+
+        // Ties go to the first reading
+        let min = (0..readings.len()).min_by_key(|idx| (readings[*idx], *idx)).map(depth);
+        let max = (0..readings.len()).max_by_key(|idx| (readings[*idx], std::cmp::Reverse(*idx))).map(depth);
+
+        Self {
+            decreases,
+            plateaus,
+            longest_run,
+            largest_jump,
+            average_window,
+            moving_average,
+            min,
+            max,
+        }
+    }
+
+    pub fn to_text(&self, statistics: &[Statistic]) -> String {
+        let mut text = String::new();
+
+        for statistic in statistics {
+            let line = match statistic {
+                Statistic::Decreases => format!("Decreases: {}", self.decreases),
+                Statistic::Plateaus => format!("Plateaus: {}", self.plateaus),
+                Statistic::LongestRun => match self.longest_run {
+                    Some(run) => format!("Longest increasing run: {} readings from line {}", run.length, run.start),
+                    None => "Longest increasing run: none".to_string(),
+                },
+                Statistic::LargestJump => match self.largest_jump {
+                    Some(jump) => format!(
+                        "Largest jump: {} from {} (line {}) to {} (line {})",
+                        jump.to.depth as i64 - jump.from.depth as i64,
+                        jump.from.depth, jump.from.position, jump.to.depth, jump.to.position
+                    ),
+                    None => "Largest jump: none".to_string(),
+                },
+                Statistic::MovingAverage => format!(
+                    "Moving average over {} readings: {}",
+                    self.average_window,
+                    self.moving_average.iter().map(|average| format!("{:.2}", average)).collect::<Vec<_>>().join(", ")
+                ),
+                Statistic::Extremes => match (self.min, self.max) {
+                    (Some(min), Some(max)) => format!(
+                        "Min depth: {} (line {})\nMax depth: {} (line {})",
+                        min.depth, min.position, max.depth, max.position
+                    ),
+                    _ => "Min depth: none\nMax depth: none".to_string(),
+                },
+            };
+
+            text += &line;
+            text += "\n";
+        }
+
+        text
+    }
+
+    pub fn to_json(&self, statistics: &[Statistic]) -> String {
+        let depth_json = |depth: Option<Depth>| match depth {
+            Some(depth) => format!(r#"{{"depth":{},"position":{}}}"#, depth.depth, depth.position),
+            None => "null".to_string(),
+        };
+
+        let fields = statistics
+            .iter()
+            .map(|statistic| match statistic {
+                Statistic::Decreases => format!(r#""decreases":{}"#, self.decreases),
+                Statistic::Plateaus => format!(r#""plateaus":{}"#, self.plateaus),
+                Statistic::LongestRun => match self.longest_run {
+                    Some(run) => format!(r#""longest_run":{{"start":{},"length":{}}}"#, run.start, run.length),
+                    None => r#""longest_run":null"#.to_string(),
+                },
+                Statistic::LargestJump => match self.largest_jump {
+                    Some(jump) => format!(r#""largest_jump":{{"from":{},"to":{}}}"#, depth_json(Some(jump.from)), depth_json(Some(jump.to))),
+                    None => r#""largest_jump":null"#.to_string(),
+                },
+                Statistic::MovingAverage => format!(
+                    r#""moving_average":{{"window":{},"values":[{}]}}"#,
+                    self.average_window,
+                    self.moving_average.iter().map(ToString::to_string).collect::<Vec<_>>().join(",")
+                ),
+                Statistic::Extremes => format!(r#""min":{},"max":{}"#, depth_json(self.min), depth_json(self.max)),
+            })
+            .collect::<Vec<_>>();
+
+        format!("{{{}}}", fields.join(","))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::{Depth, Jump, Report, Run, Statistic};
+
+    const EXAMPLE: [i32; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+
+    // Readings on lines one after the other, from the first one
+    fn report(readings: &[i32], average_window: usize) -> Report {
+        Report::new(readings, &(1..=readings.len()).collect::<Vec<_>>(), average_window)
+    }
+
+    #[test]
+    fn example_report(){
+        let report = report(&EXAMPLE, 3);
+
+        assert_eq!(report.decreases, 2);
+        assert_eq!(report.plateaus, 0);
+        // 200 207 240 269 is as long, the first run wins
+        assert_eq!(report.longest_run, Some(Run { start: 1, length: 4 }));
+        assert_eq!(report.largest_jump, Some(Jump {
+            from: Depth { depth: 207, position: 6 },
+            to: Depth { depth: 240, position: 7 },
+        }));
+        assert_eq!(report.moving_average, vec![607.0 / 3.0, 618.0 / 3.0, 618.0 / 3.0, 617.0 / 3.0, 647.0 / 3.0, 716.0 / 3.0, 769.0 / 3.0, 792.0 / 3.0]);
+        assert_eq!(report.min, Some(Depth { depth: 199, position: 1 }));
+        assert_eq!(report.max, Some(Depth { depth: 269, position: 8 }));
+    }
+
+    #[test]
+    fn plateaus_and_ties(){
+        let report = report(&[3, 3, 1, 5, 1, 5, 5], 2);
+
+        assert_eq!(report.plateaus, 2);
+        assert_eq!(report.decreases, 2);
+        // Both jumps of 4 are as large, the first one wins
+        assert_eq!(report.largest_jump.unwrap().to.position, 4);
+        assert_eq!(report.min, Some(Depth { depth: 1, position: 3 }));
+        assert_eq!(report.max, Some(Depth { depth: 5, position: 4 }));
+        assert_eq!(report.longest_run, Some(Run { start: 3, length: 2 }));
+    }
+
+    #[test]
+    fn no_readings(){
+        let report = report(&[], 3);
+        assert_eq!(report.longest_run, None);
+        assert_eq!(report.largest_jump, None);
+        assert!(report.moving_average.is_empty());
+        assert_eq!(report.to_json(&[Statistic::Extremes]), r#"{"min":null,"max":null}"#);
+    }
+
+    #[test]
+    fn selected_statistics_as_text(){
+        let report = report(&EXAMPLE, 3);
+        let statistics = ["decreases", "jump", "extremes"].iter().map(|s| Statistic::from_str(s).unwrap()).collect::<Vec<_>>();
+
+        assert_eq!(report.to_text(&statistics), concat!(
+            "Decreases: 2\n",
+            "Largest jump: 33 from 207 (line 6) to 240 (line 7)\n",
+            "Min depth: 199 (line 1)\n",
+            "Max depth: 269 (line 8)\n",
+        ));
+    }
+
+    #[test]
+    fn selected_statistics_as_json(){
+        let report = report(&[1, 2, 4], 2);
+
+        assert_eq!(
+            report.to_json(&[Statistic::Plateaus, Statistic::LongestRun, Statistic::MovingAverage]),
+            r#"{"plateaus":0,"longest_run":{"start":1,"length":3},"moving_average":{"window":2,"values":[1.5,3]}}"#
+        );
+    }
+
+    #[test]
+    fn positions_are_line_numbers(){
+        // Lines 1, 3 and 4 were left out of the readings
+        let report = Report::new(&[5, 2, 3, 9], &[2, 5, 6, 7], 2);
+
+        assert_eq!(report.min, Some(Depth { depth: 2, position: 5 }));
+        assert_eq!(report.max, Some(Depth { depth: 9, position: 7 }));
+        assert_eq!(report.longest_run, Some(Run { start: 5, length: 3 }));
+        assert_eq!(report.largest_jump, Some(Jump {
+            from: Depth { depth: 3, position: 6 },
+            to: Depth { depth: 9, position: 7 },
+        }));
+        assert_eq!(report.to_text(&[Statistic::LongestRun]), "Longest increasing run: 3 readings from line 5\n");
+    }
+
+    #[test]
+    fn unknown_statistic(){
+        assert_eq!(Statistic::from_str("median"), Err("median".to_string()));
+    }
+}
@@ -0,0 +1,146 @@
+use std::io::{BufRead, Write};
+
+use crate::input::{parse_reading, InputError, ParseOptions};
+
+// Counts the increases of the sum of `window` consecutive readings as they arrive, keeping only the
+// last `window` readings around
+pub struct IncreaseCounter {
+    window: usize,
+    // Last readings, the oldest one being at `oldest` once the ring is full
+    ring: Vec<i32>,
+    oldest: usize,
+    increases: usize,
+}
+
+impl IncreaseCounter {
+    pub fn new(window: usize) -> Self {
+        assert!(window > 0, "The window needs at least one reading");
+
+        Self {
+            window,
+            ring: Vec::with_capacity(window),
+            oldest: 0,
+            increases: 0,
+        }
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    pub fn increases(&self) -> usize {
+        self.increases
+    }
+
+    // Same comparison as `count_increases`: the reading leaving the window against the one entering it
+    pub fn push(&mut self, reading: i32) {
+        if self.ring.len() < self.window {
+            self.ring.push(reading);
+            return;
+        }
+
+        if reading > self.ring[self.oldest] {
+            self.increases += 1;
+        }
+
+        self.ring[self.oldest] = reading;
+        self.oldest = (self.oldest + 1) % self.window;
+    }
+}
+
+// Reads one depth per line until the end of the input, writing the counts every `progress_every`
+// readings if asked for. Returns the number of readings.
+pub fn analyse<R: BufRead, W: Write>(
+    input: R,
+    counters: &mut [IncreaseCounter],
+    options: &ParseOptions,
+    progress_every: Option<usize>,
+    output: &mut W,
+) -> Result<usize, InputError> {
+    let mut reading_count = 0;
+
+    for (idx, line) in input.lines().enumerate() {
+        let reading = match parse_reading(&line?, idx + 1, options)? {
+            Some(reading) => reading,
+            None => continue,
+        };
+
+        for counter in counters.iter_mut() {
+            counter.push(reading);
+        }
+        reading_count += 1;
+
+        if progress_every.is_some_and(|every| reading_count % every == 0) {
+            writeln!(output, "After {} readings: {}", reading_count, summary(counters))?;
+            // Whoever reads the other end of a pipe wants to see it now
+            output.flush()?;
+        }
+    }
+
+    Ok(reading_count)
+}
+
+pub fn summary(counters: &[IncreaseCounter]) -> String {
+    counters
+        .iter()
+        .map(|counter| format!("{} increases with window of {}", counter.increases(), counter.window()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::{BufReader, Cursor};
+
+    use super::{analyse, IncreaseCounter};
+    use crate::input::{InputError, ParseOptions};
+
+    #[test]
+    fn same_counts_as_the_whole_input(){
+        let file = BufReader::new(File::open("../files/day1/input1.txt").unwrap());
+        let mut counters = vec![IncreaseCounter::new(1), IncreaseCounter::new(3)];
+        let mut output = Vec::new();
+
+        let reading_count = analyse(file, &mut counters, &ParseOptions::default(), None, &mut output).unwrap();
+        assert_eq!(reading_count, 2000);
+        assert_eq!(counters[0].increases(), 1696);
+        assert_eq!(counters[1].increases(), 1737);
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn progress_output(){
+        let input = Cursor::new("199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n");
+        let mut counters = vec![IncreaseCounter::new(1), IncreaseCounter::new(3)];
+        let mut output = Vec::new();
+
+        analyse(input, &mut counters, &ParseOptions::default(), Some(4), &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), concat!(
+            "After 4 readings: 3 increases with window of 1, 1 increases with window of 3\n",
+            "After 8 readings: 6 increases with window of 1, 3 increases with window of 3\n",
+        ));
+        assert_eq!(counters[1].increases(), 5);
+    }
+
+    #[test]
+    fn stops_at_the_first_invalid_line(){
+        let input = Cursor::new("199\n# comment\n200\nbad\n300\n");
+        let options = ParseOptions { skip_blank_lines: false, skip_comments: true };
+        let mut counters = vec![IncreaseCounter::new(1)];
+
+        let result = analyse(input, &mut counters, &options, Some(1), &mut Vec::new());
+        assert_eq!(result, Err(InputError::InvalidReading { line: 4, content: "bad".to_string() }));
+        assert_eq!(counters[0].increases(), 1);
+    }
+
+    #[test]
+    fn ring_wraps_around(){
+        let mut counter = IncreaseCounter::new(2);
+        for reading in [5, 1, 6, 0, 7, 2, 1] {
+            counter.push(reading);
+        }
+        // 6 > 5, 7 > 6 and 2 > 0
+        assert_eq!(counter.increases(), 3);
+    }
+}
@@ -0,0 +1,213 @@
+use std::fmt::{Display, Formatter};
+
+use crate::json::Value;
+use crate::{LineChecker, SyntaxError};
+
+#[derive(Debug, PartialEq)]
+pub struct Chunk {
+    pub opener: char,
+    // None when the line ended before the chunk was closed
+    pub closer: Option<char>,
+    // Column of the opener (inclusive) to the column after the closer (exclusive). Unclosed chunks
+    // extend to the end of the line.
+    pub start: usize,
+    pub end: usize,
+    pub children: Vec<Chunk>,
+}
+
+// All the chunks of one line, only available for valid and incomplete lines
+#[derive(Debug, PartialEq)]
+pub struct ChunkTree {
+    pub chunks: Vec<Chunk>,
+}
+
+impl Chunk {
+    fn open(opener: char, start: usize) -> Self {
+        Self {
+            opener,
+            closer: None,
+            start,
+            end: start,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closer.is_some()
+    }
+
+    // A chunk without children has a depth of one
+    pub fn depth(&self) -> usize {
+        1 + self.children.iter().map(Chunk::depth).max().unwrap_or(0)
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        1 + self.children.iter().map(Chunk::chunk_count).sum::<usize>()
+    }
+
+    pub fn to_json(&self) -> Value {
+        Value::object(vec![
+            ("opener", self.opener.to_string().into()),
+            ("closer", self.closer.map(|c| c.to_string().into()).unwrap_or(Value::Null)),
+            ("span", Value::Array(vec![self.start.into(), self.end.into()])),
+            ("children", Value::Array(self.children.iter().map(Chunk::to_json).collect())),
+        ])
+    }
+
+    fn fmt_indented(&self, f: &mut Formatter<'_>, indent: usize) -> std::fmt::Result {
+        write!(f, "{:indent$}{}", "", self.opener, indent = indent * 2)?;
+
+        match self.closer {
+            Some(closer) => writeln!(f, "{} {}..{}", closer, self.start, self.end)?,
+            None => writeln!(f, "  {}..{} unclosed", self.start, self.end)?,
+        }
+
+        for child in &self.children {
+            child.fmt_indented(f, indent + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ChunkTree {
+    // Same rules as `LineChecker::check`, but the chunks are kept instead of being thrown away
+    pub fn parse(line: &str) -> Result<Self, SyntaxError> {
+        let mut chunks = Vec::new();
+        // Chunks that have been opened but not closed yet, the innermost one on top
+        let mut open_chunks: Vec<Chunk> = Vec::new();
+        let mut line_length = 0;
+
+        for (idx, symbol) in line.chars().enumerate() {
+            line_length = idx + 1;
+
+            match symbol {
+                '<' | '[' | '{' | '(' => open_chunks.push(Chunk::open(symbol, idx)),
+                '>' | ']' | '}' | ')' => {
+                    let mut chunk = match open_chunks.pop() {
+                        Some(chunk) => chunk,
+                        None => return Err(SyntaxError::UnmatchedClosingChar { got: symbol, column: idx }),
+                    };
+
+                    let expected = LineChecker::closing_symbol_for(chunk.opener).unwrap();
+                    if symbol != expected {
+                        return Err(SyntaxError::UnexpectedClosingChar { expected, got: symbol, column: idx });
+                    }
+
+                    chunk.closer = Some(symbol);
+                    chunk.end = idx + 1;
+
+                    match open_chunks.last_mut() {
+                        Some(parent) => parent.children.push(chunk),
+                        None => chunks.push(chunk),
+                    }
+                },
+                _ => return Err(SyntaxError::UnknownSymbol { got: symbol, column: idx }),
+            }
+        }
+
+        // Whatever is still open runs until the end of the line
+        while let Some(mut chunk) = open_chunks.pop() {
+            chunk.end = line_length;
+
+            match open_chunks.last_mut() {
+                Some(parent) => parent.children.push(chunk),
+                None => chunks.push(chunk),
+            }
+        }
+
+        Ok(Self { chunks })
+    }
+
+    pub fn is_complete(&self) -> bool {
+        fn all_closed(chunks: &[Chunk]) -> bool {
+            chunks.iter().all(|chunk| chunk.is_closed() && all_closed(&chunk.children))
+        }
+
+        all_closed(&self.chunks)
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.chunks.iter().map(Chunk::depth).max().unwrap_or(0)
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.iter().map(Chunk::chunk_count).sum()
+    }
+
+    pub fn to_json(&self) -> Value {
+        Value::object(vec![
+            ("complete", self.is_complete().into()),
+            ("max_depth", self.max_depth().into()),
+            ("chunk_count", self.chunk_count().into()),
+            ("chunks", Value::Array(self.chunks.iter().map(Chunk::to_json).collect())),
+        ])
+    }
+}
+
+impl Display for ChunkTree {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for chunk in &self.chunks {
+            chunk.fmt_indented(f, 0)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ChunkTree;
+
+    macro_rules! statistics_test {
+        ($name:ident, $line:literal -> depth $depth:literal, count $count:literal, complete $complete:literal) => {
+            #[test]
+            fn $name(){
+                let tree = ChunkTree::parse($line).expect("Line should not be corrupted");
+                assert_eq!(tree.max_depth(), $depth);
+                assert_eq!(tree.chunk_count(), $count);
+                assert_eq!(tree.is_complete(), $complete);
+            }
+        };
+    }
+
+    statistics_test!(empty_line, "" -> depth 0, count 0, complete true);
+    statistics_test!(single_chunk, "()" -> depth 1, count 1, complete true);
+    statistics_test!(siblings, "()[]<>" -> depth 1, count 3, complete true);
+    statistics_test!(nested, "{()()()}" -> depth 2, count 4, complete true);
+    statistics_test!(deeply_nested, "[<>({}){}[([])<>]]" -> depth 4, count 9, complete true);
+    statistics_test!(incomplete, "[({(<(())[]>[[{[]{<()<>>" -> depth 10, count 16, complete false);
+
+    #[test]
+    fn spans_of_incomplete_line(){
+        let tree = ChunkTree::parse("[(<>").unwrap();
+        let outer = &tree.chunks[0];
+        assert_eq!((outer.start, outer.end, outer.closer), (0, 4, None));
+
+        let middle = &outer.children[0];
+        assert_eq!((middle.start, middle.end, middle.closer), (1, 4, None));
+
+        let inner = &middle.children[0];
+        assert_eq!((inner.start, inner.end, inner.closer), (2, 4, Some('>')));
+    }
+
+    #[test]
+    fn corrupted_line_has_no_tree(){
+        assert!(ChunkTree::parse("{([(<{}[<>[]}>{[]{[(<()>").is_err());
+    }
+
+    #[test]
+    fn pretty_print(){
+        let tree = ChunkTree::parse("{()[").unwrap();
+        assert_eq!(tree.to_string(), "{  0..4 unclosed\n  () 1..3\n  [  3..4 unclosed\n");
+    }
+
+    #[test]
+    fn json_dump(){
+        let tree = ChunkTree::parse("(<>").unwrap();
+        assert_eq!(
+            tree.to_json().to_string(),
+            r#"{"complete":false,"max_depth":2,"chunk_count":2,"chunks":[{"opener":"(","closer":null,"span":[0,3],"children":[{"opener":"<","closer":">","span":[1,3],"children":[]}]}]}"#
+        );
+    }
+}
@@ -0,0 +1,417 @@
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter, Write};
+use std::iter::Peekable;
+use std::str::Chars;
+
+// Just enough JSON to talk to an editor and dump our own structures, no need to pull a whole crate for that
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    // Kept apart from the other numbers, scores don't fit in the 53 bits of precision of a f64
+    Integer(i128),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    // Keys are kept in insertion order so the output stays readable
+    Object(Vec<(String, Value)>),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    UnexpectedEnd,
+    UnexpectedChar { got: char, position: usize },
+    InvalidNumber(String),
+    InvalidEscape(char),
+    TrailingCharacters { position: usize },
+}
+
+struct Parser<'input> {
+    chars: Peekable<Chars<'input>>,
+    position: usize,
+}
+
+impl Value {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser {
+            chars: input.chars().peekable(),
+            position: 0,
+        };
+
+        let value = parser.parse_value()?;
+        parser.skip_whitespace();
+
+        if parser.chars.peek().is_some() {
+            return Err(ParseError::TrailingCharacters { position: parser.position });
+        }
+
+        Ok(value)
+    }
+
+    pub fn object(entries: Vec<(&str, Value)>) -> Self {
+        Value::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect()
+        )
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(entries) => entries
+                .iter()
+                .find(|(entry_key, _)| entry_key == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::Integer(n) => u64::try_from(*n).ok(),
+            Value::Number(n) if *n >= 0.0 && n.fract() == 0.0 => Some(*n as u64),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::String(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::String(s)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+macro_rules! value_from_integer {
+    ($($integer_type:ty),*) => {
+        $(
+            impl From<$integer_type> for Value {
+                fn from(n: $integer_type) -> Self {
+                    Value::Integer(n as i128)
+                }
+            }
+        )*
+    };
+}
+
+value_from_integer!(u32, u64, usize, i64);
+
+impl From<f64> for Value {
+    fn from(n: f64) -> Self {
+        Value::Number(n)
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Integer(n) => write!(f, "{}", n),
+            // Integers should not end up with a trailing ".0"
+            // JSON has no infinity nor NaN
+            Value::Number(n) if !n.is_finite() => f.write_str("null"),
+            Value::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => write!(f, "{}", *n as i64),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::String(s) => write_escaped(f, s),
+            Value::Array(items) => {
+                f.write_char('[')?;
+                for (idx, item) in items.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_char(']')
+            },
+            Value::Object(entries) => {
+                f.write_char('{')?;
+                for (idx, (key, value)) in entries.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_escaped(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_char('}')
+            },
+        }
+    }
+}
+
+fn write_escaped(f: &mut Formatter<'_>, s: &str) -> std::fmt::Result {
+    f.write_char('"')?;
+
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+
+    f.write_char('"')
+}
+
+impl<'input> Parser<'input> {
+    fn next(&mut self) -> Result<char, ParseError> {
+        let c = self.chars.next().ok_or(ParseError::UnexpectedEnd)?;
+        self.position += 1;
+        Ok(c)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        let got = self.next()?;
+        if got != expected {
+            return Err(ParseError::UnexpectedChar { got, position: self.position - 1 });
+        }
+
+        Ok(())
+    }
+
+    fn expect_keyword(&mut self, keyword: &str, value: Value) -> Result<Value, ParseError> {
+        for c in keyword.chars() {
+            self.expect(c)?;
+        }
+
+        Ok(value)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.chars.peek() {
+            self.chars.next();
+            self.position += 1;
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Value, ParseError> {
+        self.skip_whitespace();
+
+        match *self.chars.peek().ok_or(ParseError::UnexpectedEnd)? {
+            'n' => self.expect_keyword("null", Value::Null),
+            't' => self.expect_keyword("true", Value::Bool(true)),
+            'f' => self.expect_keyword("false", Value::Bool(false)),
+            '"' => self.parse_string().map(Value::String),
+            '[' => self.parse_array(),
+            '{' => self.parse_object(),
+            '-' | '0'..='9' => self.parse_number(),
+            got => Err(ParseError::UnexpectedChar { got, position: self.position }),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, ParseError> {
+        self.expect('"')?;
+        let mut s = String::new();
+
+        loop {
+            match self.next()? {
+                '"' => return Ok(s),
+                '\\' => match self.next()? {
+                    '"' => s.push('"'),
+                    '\\' => s.push('\\'),
+                    '/' => s.push('/'),
+                    'b' => s.push('\u{8}'),
+                    'f' => s.push('\u{c}'),
+                    'n' => s.push('\n'),
+                    'r' => s.push('\r'),
+                    't' => s.push('\t'),
+                    'u' => s.push(self.parse_unicode_escape()?),
+                    other => return Err(ParseError::InvalidEscape(other)),
+                },
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn parse_unicode_escape(&mut self) -> Result<char, ParseError> {
+        let high = self.parse_hex_quad()?;
+
+        // Characters outside the BMP come as a surrogate pair: \uD83D\uDE00
+        if (0xD800..0xDC00).contains(&high) {
+            self.expect('\\')?;
+            self.expect('u')?;
+            let low = self.parse_hex_quad()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(ParseError::InvalidEscape('u'));
+            }
+
+            let code_point = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+            return char::from_u32(code_point).ok_or(ParseError::InvalidEscape('u'));
+        }
+
+        char::from_u32(high).ok_or(ParseError::InvalidEscape('u'))
+    }
+
+    fn parse_hex_quad(&mut self) -> Result<u32, ParseError> {
+        let mut code = 0;
+
+        for _ in 0..4 {
+            let c = self.next()?;
+            let digit = c.to_digit(16).ok_or(ParseError::UnexpectedChar { got: c, position: self.position - 1 })?;
+            code = code * 16 + digit;
+        }
+
+        Ok(code)
+    }
+
+    fn parse_number(&mut self) -> Result<Value, ParseError> {
+        let mut raw = String::new();
+
+        while let Some(&c) = self.chars.peek() {
+            if !matches!(c, '-' | '+' | '.' | 'e' | 'E' | '0'..='9') {
+                break;
+            }
+
+            raw.push(c);
+            self.chars.next();
+            self.position += 1;
+        }
+
+        // Integers too large for an i128 still make sense as a f64
+        if let Ok(n) = raw.parse::<i128>() {
+            return Ok(Value::Integer(n));
+        }
+
+        // Numbers too large for a f64 like 1e999 would come out as `inf`, which isn't JSON
+        match raw.parse::<f64>() {
+            Ok(n) if n.is_finite() => Ok(Value::Number(n)),
+            _ => Err(ParseError::InvalidNumber(raw)),
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Value, ParseError> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&']') {
+            self.next()?;
+            return Ok(Value::Array(items));
+        }
+
+        loop {
+            items.push(self.parse_value()?);
+            self.skip_whitespace();
+
+            match self.next()? {
+                ',' => continue,
+                ']' => return Ok(Value::Array(items)),
+                got => return Err(ParseError::UnexpectedChar { got, position: self.position - 1 }),
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Value, ParseError> {
+        self.expect('{')?;
+        let mut entries = Vec::new();
+
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&'}') {
+            self.next()?;
+            return Ok(Value::Object(entries));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            entries.push((key, self.parse_value()?));
+            self.skip_whitespace();
+
+            match self.next()? {
+                ',' => continue,
+                '}' => return Ok(Value::Object(entries)),
+                got => return Err(ParseError::UnexpectedChar { got, position: self.position - 1 }),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ParseError, Value};
+
+    macro_rules! roundtrip_test {
+        ($name:ident, $input:literal) => {
+            #[test]
+            fn $name(){
+                let value = Value::parse($input).expect("Valid JSON should parse");
+                assert_eq!(value.to_string(), $input);
+            }
+        };
+    }
+
+    roundtrip_test!(roundtrip_null, "null");
+    roundtrip_test!(roundtrip_integer, "42");
+    roundtrip_test!(roundtrip_large_integer, "149011611938476562");
+    roundtrip_test!(roundtrip_negative_float, "-1.5");
+    roundtrip_test!(roundtrip_escaped_string, r#""a\"b\\c\nd""#);
+    roundtrip_test!(roundtrip_nested, r#"{"a":[1,true,{"b":null}],"c":"d"}"#);
+
+    #[test]
+    fn parse_with_whitespace_and_unicode_escape(){
+        let value = Value::parse(" { \"key\" : [ \"\\u00e9\" , 1 ] } ").unwrap();
+        assert_eq!(value.get("key").and_then(|v| v.as_array()).map(|a| a.len()), Some(2));
+        assert_eq!(value.get("key").unwrap().as_array().unwrap()[0].as_str(), Some("é"));
+    }
+
+    #[test]
+    fn integers_stay_exact(){
+        assert_eq!(Value::from(u64::MAX).to_string(), "18446744073709551615");
+        assert_eq!(Value::parse("18446744073709551615").unwrap().as_u64(), Some(u64::MAX));
+        assert_eq!(Value::parse("2.0").unwrap().as_u64(), Some(2));
+        assert_eq!(Value::parse("-1").unwrap().as_u64(), None);
+    }
+
+    #[test]
+    fn non_finite_numbers(){
+        assert_eq!(Value::parse("1e999"), Err(ParseError::InvalidNumber("1e999".to_string())));
+        assert_eq!(Value::parse("[-1e999]"), Err(ParseError::InvalidNumber("-1e999".to_string())));
+        assert_eq!(Value::from(f64::NAN).to_string(), "null");
+        assert_eq!(Value::from(f64::INFINITY).to_string(), "null");
+    }
+
+    #[test]
+    fn surrogate_pair(){
+        assert_eq!(Value::parse(r#""\uD83D\uDE00""#), Ok(Value::String("\u{1F600}".to_string())));
+        // The high surrogate must be followed by a low one
+        assert_eq!(Value::parse(r#""\uD83D\u0041""#), Err(ParseError::InvalidEscape('u')));
+    }
+
+    #[test]
+    fn reject_trailing_characters(){
+        assert!(Value::parse("{} x").is_err());
+    }
+}
@@ -0,0 +1,441 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+use crate::json::Value;
+use crate::{LineChecker, SyntaxError};
+
+// Minimal subset of the language server protocol, enough for an editor to show the syntax errors
+// of a document and to propose the closing sequence of an incomplete line.
+// See https://microsoft.github.io/language-server-protocol/specifications/specification-current/
+
+const DIAGNOSTIC_SOURCE: &str = "day10";
+
+const SEVERITY_ERROR: u32 = 1;
+const SEVERITY_WARNING: u32 = 2;
+
+const TEXT_DOCUMENT_SYNC_FULL: u32 = 1;
+const COMPLETION_ITEM_KIND_TEXT: u32 = 1;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+struct Server<W: Write> {
+    output: W,
+    // Full text of each open document, indexed by URI
+    documents: HashMap<String, String>,
+}
+
+pub fn serve<R: BufRead, W: Write>(mut input: R, output: W) -> io::Result<()> {
+    let mut server = Server {
+        output,
+        documents: HashMap::new(),
+    };
+
+    while let Some(content) = read_message(&mut input)? {
+        let message = match Value::parse(&content) {
+            Ok(message) => message,
+            Err(e) => {
+                server.send_error(Value::Null, PARSE_ERROR, &format!("{:?}", e))?;
+                continue;
+            }
+        };
+
+        if !server.handle(&message)? {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+// Each message is preceded by headers, of which only Content-Length matters for us
+fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<String>> {
+    let mut content_length = None;
+
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+
+            // Stray blank line between two messages
+            continue;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                let length = value.trim().parse::<usize>()
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                content_length = Some(length);
+            }
+        }
+    }
+
+    let mut content = vec![0; content_length.unwrap_or(0)];
+    input.read_exact(&mut content)?;
+
+    String::from_utf8(content)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+impl<W: Write> Server<W> {
+    // Returns false once the editor asked us to exit
+    fn handle(&mut self, message: &Value) -> io::Result<bool> {
+        let id = message.get("id").cloned();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let method = match message.get("method").and_then(Value::as_str) {
+            Some(method) => method,
+            None => {
+                // Responses to requests we never sent, nothing to do with them
+                if let Some(id) = id {
+                    if message.get("result").is_none() && message.get("error").is_none() {
+                        self.send_error(id, INVALID_REQUEST, "Missing method")?;
+                    }
+                }
+
+                return Ok(true);
+            }
+        };
+
+        match (method, id) {
+            ("initialize", Some(id)) => {
+                let capabilities = Value::object(vec![
+                    ("textDocumentSync", TEXT_DOCUMENT_SYNC_FULL.into()),
+                    ("completionProvider", Value::object(vec![])),
+                ]);
+
+                let result = Value::object(vec![
+                    ("capabilities", capabilities),
+                    ("serverInfo", Value::object(vec![("name", DIAGNOSTIC_SOURCE.into())])),
+                ]);
+
+                self.send_result(id, result)?;
+            },
+
+            ("shutdown", Some(id)) => self.send_result(id, Value::Null)?,
+            ("exit", None) => return Ok(false),
+
+            ("textDocument/didOpen", None) => {
+                let document = params.get("textDocument");
+                let uri = document.and_then(|d| d.get("uri")).and_then(Value::as_str);
+                let text = document.and_then(|d| d.get("text")).and_then(Value::as_str);
+
+                if let (Some(uri), Some(text)) = (uri, text) {
+                    self.documents.insert(uri.to_string(), text.to_string());
+                    self.publish_diagnostics(uri)?;
+                }
+            },
+
+            ("textDocument/didChange", None) => {
+                let uri = params.get("textDocument").and_then(|d| d.get("uri")).and_then(Value::as_str);
+
+                // We announced full synchronisation, so the last change holds the whole document
+                let text = params.get("contentChanges")
+                    .and_then(Value::as_array)
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change.get("text"))
+                    .and_then(Value::as_str);
+
+                if let (Some(uri), Some(text)) = (uri, text) {
+                    self.documents.insert(uri.to_string(), text.to_string());
+                    self.publish_diagnostics(uri)?;
+                }
+            },
+
+            ("textDocument/didClose", None) => {
+                let uri = params.get("textDocument").and_then(|d| d.get("uri")).and_then(Value::as_str);
+
+                if let Some(uri) = uri {
+                    self.documents.remove(uri);
+                    // Clear whatever the editor is still displaying for that document
+                    self.send_notification("textDocument/publishDiagnostics", Value::object(vec![
+                        ("uri", uri.into()),
+                        ("diagnostics", Value::Array(vec![])),
+                    ]))?;
+                }
+            },
+
+            ("textDocument/completion", Some(id)) => {
+                let uri = params.get("textDocument").and_then(|d| d.get("uri")).and_then(Value::as_str);
+                let position = params.get("position");
+                let line = position.and_then(|p| p.get("line")).and_then(Value::as_u64);
+                let character = position.and_then(|p| p.get("character")).and_then(Value::as_u64);
+
+                match (uri, line, character) {
+                    (Some(uri), Some(line), Some(character)) => {
+                        let items = self.documents
+                            .get(uri)
+                            .map(|text| completion_items(text, line as usize, character as usize))
+                            .unwrap_or_default();
+
+                        self.send_result(id, Value::Array(items))?;
+                    },
+                    _ => self.send_error(id, INVALID_PARAMS, "Expected a text document and a position")?,
+                }
+            },
+
+            (method, Some(id)) => self.send_error(id, METHOD_NOT_FOUND, &format!("Unsupported method {}", method))?,
+
+            // Notifications we don't care about, like `initialized`
+            (_, None) => {},
+        }
+
+        Ok(true)
+    }
+
+    fn publish_diagnostics(&mut self, uri: &str) -> io::Result<()> {
+        let diagnostics = self.documents
+            .get(uri)
+            .map(|text| diagnostics(text))
+            .unwrap_or_default();
+
+        self.send_notification("textDocument/publishDiagnostics", Value::object(vec![
+            ("uri", uri.into()),
+            ("diagnostics", Value::Array(diagnostics)),
+        ]))
+    }
+
+    fn send_result(&mut self, id: Value, result: Value) -> io::Result<()> {
+        self.send(Value::object(vec![
+            ("jsonrpc", "2.0".into()),
+            ("id", id),
+            ("result", result),
+        ]))
+    }
+
+    fn send_error(&mut self, id: Value, code: i64, message: &str) -> io::Result<()> {
+        self.send(Value::object(vec![
+            ("jsonrpc", "2.0".into()),
+            ("id", id),
+            ("error", Value::object(vec![
+                ("code", code.into()),
+                ("message", message.into()),
+            ])),
+        ]))
+    }
+
+    fn send_notification(&mut self, method: &str, params: Value) -> io::Result<()> {
+        self.send(Value::object(vec![
+            ("jsonrpc", "2.0".into()),
+            ("method", method.into()),
+            ("params", params),
+        ]))
+    }
+
+    fn send(&mut self, message: Value) -> io::Result<()> {
+        let content = message.to_string();
+        write!(self.output, "Content-Length: {}\r\n\r\n{}", content.len(), content)?;
+        self.output.flush()
+    }
+}
+
+fn diagnostics(text: &str) -> Vec<Value> {
+    let mut diagnostics = Vec::new();
+
+    for (line_number, line) in text.lines().enumerate() {
+        let checker = LineChecker::new(line);
+
+        let (column, length, severity, message) = match checker.check() {
+            Ok(_) => continue,
+            Err(SyntaxError::UnexpectedClosingChar { expected, got, column }) =>
+                (column, 1, SEVERITY_ERROR, format!("Expected {}, got {}", expected, got)),
+            Err(SyntaxError::UnmatchedClosingChar { got, column }) =>
+                (column, 1, SEVERITY_ERROR, format!("Nothing to close with {}", got)),
+            Err(SyntaxError::UnknownSymbol { got, column }) =>
+                (column, 1, SEVERITY_ERROR, format!("Unknown symbol {}", got)),
+            Err(SyntaxError::IncompleteLine { stack }) => {
+                let (completed_line, _) = checker.complete(Some(&stack)).expect("Incomplete lines can be completed");
+                let message = format!("Incomplete line, missing {}", &completed_line[line.len()..]);
+                (line.chars().count(), 0, SEVERITY_WARNING, message)
+            },
+        };
+
+        let start = utf16_column(line, column);
+        let end = utf16_column(line, column + length);
+
+        diagnostics.push(Value::object(vec![
+            ("range", range(line_number, start, end)),
+            ("severity", severity.into()),
+            ("source", DIAGNOSTIC_SOURCE.into()),
+            ("message", message.into()),
+        ]));
+    }
+
+    diagnostics
+}
+
+fn completion_items(text: &str, line: usize, character: usize) -> Vec<Value> {
+    let line_content = match text.lines().nth(line) {
+        Some(line_content) => line_content,
+        None => return Vec::new(),
+    };
+
+    // Only what is before the cursor needs to be closed
+    let prefix = &line_content[..byte_offset(line_content, character)];
+
+    let (completed_line, score) = match LineChecker::new(prefix).complete(None) {
+        Some(completion) => completion,
+        None => return Vec::new(),
+    };
+
+    let closing_sequence = &completed_line[prefix.len()..];
+    if closing_sequence.is_empty() {
+        return Vec::new();
+    }
+
+    vec![Value::object(vec![
+        ("label", closing_sequence.into()),
+        ("kind", COMPLETION_ITEM_KIND_TEXT.into()),
+        ("detail", match score {
+            Some(score) => format!("Completion score {}", score).into(),
+            None => "Completion score too large for 64 bits".into(),
+        }),
+        ("insertText", closing_sequence.into()),
+    ])]
+}
+
+fn range(line: usize, start: usize, end: usize) -> Value {
+    Value::object(vec![
+        ("start", Value::object(vec![("line", line.into()), ("character", start.into())])),
+        ("end", Value::object(vec![("line", line.into()), ("character", end.into())])),
+    ])
+}
+
+// The protocol counts columns in UTF-16 code units while the checker counts characters
+fn utf16_column(line: &str, char_column: usize) -> usize {
+    line.chars().take(char_column).map(char::len_utf16).sum()
+}
+
+fn byte_offset(line: &str, utf16_column: usize) -> usize {
+    let mut current_column = 0;
+
+    for (byte_idx, c) in line.char_indices() {
+        if current_column >= utf16_column {
+            return byte_idx;
+        }
+
+        current_column += c.len_utf16();
+    }
+
+    line.len()
+}
+
+#[cfg(test)]
+mod test {
+    use super::serve;
+    use crate::json::Value;
+
+    fn frame(content: &str) -> String {
+        format!("Content-Length: {}\r\n\r\n{}", content.len(), content)
+    }
+
+    // Pipe the scripted messages through the server and collect everything it answered
+    fn run_session(messages: &[&str]) -> Vec<Value> {
+        let input = messages.iter().map(|m| frame(m)).collect::<String>();
+        let mut output = Vec::new();
+        serve(input.as_bytes(), &mut output).expect("Session should not fail");
+
+        let output = String::from_utf8(output).unwrap();
+        output
+            .split("Content-Length: ")
+            .filter(|m| !m.is_empty())
+            .map(|m| {
+                let (_, content) = m.split_once("\r\n\r\n").unwrap();
+                Value::parse(content).expect("Server should send valid JSON")
+            })
+            .collect()
+    }
+
+    fn diagnostic_messages(notification: &Value) -> Vec<String> {
+        notification.get("params").unwrap().get("diagnostics").unwrap()
+            .as_array().unwrap()
+            .iter()
+            .map(|d| d.get("message").unwrap().as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn initialize_announces_capabilities(){
+        let responses = run_session(&[r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#]);
+        let capabilities = responses[0].get("result").unwrap().get("capabilities").unwrap();
+        assert_eq!(capabilities.get("textDocumentSync").and_then(Value::as_u64), Some(1));
+        assert!(capabilities.get("completionProvider").is_some());
+    }
+
+    #[test]
+    fn did_open_and_change_publish_diagnostics(){
+        let responses = run_session(&[
+            r#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///a","languageId":"text","version":1,"text":"[]\n{([(<{}[<>[]}>{[]{[(<()>\n[({(<(())[]>[[{[]{<()<>>"}}}"#,
+            r#"{"jsonrpc":"2.0","method":"textDocument/didChange","params":{"textDocument":{"uri":"file:///a","version":2},"contentChanges":[{"text":"<>"}]}}"#,
+        ]);
+
+        assert_eq!(responses.len(), 2);
+        assert_eq!(diagnostic_messages(&responses[0]), vec![
+            "Expected ], got }".to_string(),
+            "Incomplete line, missing }}]])})]".to_string(),
+        ]);
+
+        let corrupted_start = responses[0].get("params").unwrap().get("diagnostics").unwrap().as_array().unwrap()[0]
+            .get("range").unwrap().get("start").unwrap();
+        assert_eq!(corrupted_start.get("line").and_then(Value::as_u64), Some(1));
+        assert_eq!(corrupted_start.get("character").and_then(Value::as_u64), Some(12));
+
+        assert!(diagnostic_messages(&responses[1]).is_empty());
+    }
+
+    #[test]
+    fn completion_returns_closing_sequence(){
+        let responses = run_session(&[
+            r#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///a","text":"[({(<(())[]>[[{[]{<()<>>"}}}"#,
+            r#"{"jsonrpc":"2.0","id":2,"method":"textDocument/completion","params":{"textDocument":{"uri":"file:///a"},"position":{"line":0,"character":24}}}"#,
+            r#"{"jsonrpc":"2.0","id":3,"method":"textDocument/completion","params":{"textDocument":{"uri":"file:///a"},"position":{"line":0,"character":2}}}"#,
+        ]);
+
+        let items = responses[1].get("result").unwrap().as_array().unwrap();
+        assert_eq!(items[0].get("insertText").and_then(Value::as_str), Some("}}]])})]"));
+        assert_eq!(items[0].get("detail").and_then(Value::as_str), Some("Completion score 288957"));
+
+        let items = responses[2].get("result").unwrap().as_array().unwrap();
+        assert_eq!(items[0].get("insertText").and_then(Value::as_str), Some(")]"));
+    }
+
+    #[test]
+    fn deeply_unclosed_line(){
+        let text = "(".repeat(30);
+        let did_open = format!(r#"{{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{{"textDocument":{{"uri":"file:///a","text":"{}"}}}}}}"#, text);
+        let responses = run_session(&[
+            &did_open,
+            r#"{"jsonrpc":"2.0","id":2,"method":"textDocument/completion","params":{"textDocument":{"uri":"file:///a"},"position":{"line":0,"character":30}}}"#,
+        ]);
+
+        assert_eq!(diagnostic_messages(&responses[0]), vec![format!("Incomplete line, missing {}", ")".repeat(30))]);
+
+        let items = responses[1].get("result").unwrap().as_array().unwrap();
+        assert_eq!(items[0].get("insertText").and_then(Value::as_str), Some(")".repeat(30).as_str()));
+        assert_eq!(items[0].get("detail").and_then(Value::as_str), Some("Completion score too large for 64 bits"));
+    }
+
+    #[test]
+    fn unknown_request_and_exit(){
+        let responses = run_session(&[
+            r#"{"jsonrpc":"2.0","id":4,"method":"textDocument/hover","params":{}}"#,
+            r#"{"jsonrpc":"2.0","id":5,"method":"shutdown"}"#,
+            r#"{"jsonrpc":"2.0","method":"exit"}"#,
+            r#"{"jsonrpc":"2.0","id":6,"method":"shutdown"}"#,
+        ]);
+
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0].get("error").unwrap().get("code"), Some(&Value::Integer(-32601)));
+        assert_eq!(responses[1].get("result"), Some(&Value::Null));
+    }
+}
@@ -1,8 +1,16 @@
-use std::borrow::Cow;
 use std::env::args;
 use std::fmt::{Display, Formatter};
 use std::fs;
-use std::ops::{Add, AddAssign};
+use std::io;
+
+use chunk::ChunkTree;
+use json::Value;
+use report::Report;
+
+mod chunk;
+mod json;
+mod lsp;
+mod report;
 
 const ILLEGAL_PARENTHESIS_SCORE: u32 = 3;
 const ILLEGAL_SQUARE_BRACKET_SCORE: u32 = 57;
@@ -14,9 +22,15 @@ const COMPLETION_SQUARE_BRACKET_SCORE: u64 = 2;
 const COMPLETION_CURLY_BRACKET_SCORE: u64 = 3;
 const COMPLETION_ANGLE_BRACKET_SCORE: u64 = 4;
 
+const USAGE: &str = "USAGE: day10 [--tree|--report [--json]] <input file> | day10 --lsp";
+
+#[derive(Debug)]
 enum SyntaxError {
     IncompleteLine { stack: Vec<char> },
-    UnexpectedClosingChar { expected: char, got: char, stack: Vec<char> }
+    UnexpectedClosingChar { expected: char, got: char, column: usize },
+    // Closing symbol without anything left to close
+    UnmatchedClosingChar { got: char, column: usize },
+    UnknownSymbol { got: char, column: usize },
 }
 
 #[derive(Debug)]
@@ -50,6 +64,24 @@ impl SyntaxViolationsContainer {
             _ => unreachable!("Unknown character {}", character)
         }
     }
+
+    fn get(&self, character: char) -> u32 {
+        match character {
+            '>' | '<' => self.angle_bracket,
+            '}' | '{' => self.curly_bracket,
+            ']' | '[' => self.square_bracket,
+            ')' | '(' => self.parenthesis,
+            _ => unreachable!("Unknown character {}", character)
+        }
+    }
+
+    // Counted in 64 bits, where even u32::MAX symbols of each kind fit
+    fn score(&self) -> u64 {
+        self.parenthesis as u64 * ILLEGAL_PARENTHESIS_SCORE as u64 +
+            self.square_bracket as u64 * ILLEGAL_SQUARE_BRACKET_SCORE as u64 +
+            self.curly_bracket as u64 * ILLEGAL_CURLY_BRACKET_SCORE as u64 +
+            self.angle_bracket as u64 * ILLEGAL_ANGLE_BRACKET_SCORE as u64
+    }
 }
 
 impl<'line> LineChecker<'line> {
@@ -66,17 +98,20 @@ impl<'line> LineChecker<'line> {
             let is_opening = match symbol {
                 '<' | '[' | '{' | '(' => true,
                 '>' | ']' | '}' | ')' => false,
-                _ => unreachable!("Unknown symbol {} in col {}", symbol, idx),
+                _ => return Err(SyntaxError::UnknownSymbol { got: symbol, column: idx }),
             };
 
             if is_opening {
                 stack.push(symbol);
             } else {
-                let opening_symbol = stack.pop().expect("Unexpected empty stack");
+                let opening_symbol = match stack.pop() {
+                    Some(opening_symbol) => opening_symbol,
+                    None => return Err(SyntaxError::UnmatchedClosingChar { got: symbol, column: idx }),
+                };
                 let expected_closing_symbol = Self::closing_symbol_for(opening_symbol).unwrap();
 
                 if symbol != expected_closing_symbol {
-                    return Err(SyntaxError::UnexpectedClosingChar { expected: expected_closing_symbol, got: symbol, stack })
+                    return Err(SyntaxError::UnexpectedClosingChar { expected: expected_closing_symbol, got: symbol, column: idx })
                 }
             }
         }
@@ -88,10 +123,11 @@ impl<'line> LineChecker<'line> {
         Ok(())
     }
 
-    // Option of the completed line and the score
-    pub fn complete(&self, stack: Option<&[char]>) -> Option<(String, u64)> {
+    // Option of the completed line and the score. The score grows five times with each missing symbol,
+    // it is None once it doesn't fit in 64 bits.
+    pub fn complete(&self, stack: Option<&[char]>) -> Option<(String, Option<u64>)> {
         let mut completed_line = self.line.to_string();
-        let mut score = 0;
+        let mut score = Some(0u64);
 
         let mut stack = match stack {
             Some(stack) => stack.to_vec(),
@@ -99,7 +135,7 @@ impl<'line> LineChecker<'line> {
                 Ok(_) => return Some((completed_line, score)),
                 Err(e) => match e {
                     SyntaxError::IncompleteLine { stack } => stack,
-                    SyntaxError::UnexpectedClosingChar { .. } => return None
+                    _ => return None
                 }
             }
         };
@@ -111,20 +147,34 @@ impl<'line> LineChecker<'line> {
             let mut buf = [0; 1];
             completed_line += closing_symbol.encode_utf8(&mut buf);
 
-            let score_to_add = match closing_symbol {
-                '>' => COMPLETION_ANGLE_BRACKET_SCORE,
-                ']' => COMPLETION_SQUARE_BRACKET_SCORE,
-                '}' => COMPLETION_CURLY_BRACKET_SCORE,
-                ')' => COMPLETION_PARENTHESIS_SCORE,
-                _ => unreachable!()
-            };
-
-            score = score * 5 + score_to_add;
+            score = score
+                .and_then(|score| score.checked_mul(5))
+                .and_then(|score| score.checked_add(Self::completion_score_for(closing_symbol)));
         };
 
         Some((completed_line, score))
     }
 
+    pub fn illegal_score_for(closing_symbol: char) -> u32 {
+        match closing_symbol {
+            '>' => ILLEGAL_ANGLE_BRACKET_SCORE,
+            ']' => ILLEGAL_SQUARE_BRACKET_SCORE,
+            '}' => ILLEGAL_CURLY_BRACKET_SCORE,
+            ')' => ILLEGAL_PARENTHESIS_SCORE,
+            _ => unreachable!("Unknown closing symbol {}", closing_symbol)
+        }
+    }
+
+    pub fn completion_score_for(closing_symbol: char) -> u64 {
+        match closing_symbol {
+            '>' => COMPLETION_ANGLE_BRACKET_SCORE,
+            ']' => COMPLETION_SQUARE_BRACKET_SCORE,
+            '}' => COMPLETION_CURLY_BRACKET_SCORE,
+            ')' => COMPLETION_PARENTHESIS_SCORE,
+            _ => unreachable!("Unknown closing symbol {}", closing_symbol)
+        }
+    }
+
     pub fn closing_symbol_for(symbol: char) -> Option<char> {
         match symbol {
             '<' => Some('>'),
@@ -143,8 +193,35 @@ impl<'line> Display for LineChecker<'line> {
 }
 
 fn main() {
-    let filename = args().nth(1).expect("USAGE: day8 <input file>");
+    let arguments = args().skip(1).collect::<Vec<_>>();
+
+    if arguments.iter().any(|arg| arg == "--lsp") {
+        let stdin = io::stdin();
+        let stdout = io::stdout();
+        lsp::serve(stdin.lock(), stdout.lock()).expect("I/O error while talking to the editor");
+        return;
+    }
+
+    let filename = arguments.iter().find(|arg| !arg.starts_with("--")).expect(USAGE);
     let content = fs::read_to_string(filename).unwrap();
+
+    if arguments.iter().any(|arg| arg == "--tree") {
+        dump_trees(&content, arguments.iter().any(|arg| arg == "--json"));
+        return;
+    }
+
+    if arguments.iter().any(|arg| arg == "--report") {
+        let report = Report::new(&content);
+
+        if arguments.iter().any(|arg| arg == "--json") {
+            println!("{}", report.to_json());
+        } else {
+            print!("{}", report);
+        }
+
+        return;
+    }
+
     let lines = content
         .lines()
         .map(LineChecker::new)
@@ -175,18 +252,21 @@ fn part1(lines: &[LineChecker]) {
                     println!("ERR: expected {}, got {}", expected, got);
                     syntax_error_symbols.increment(got);
                     false
+                },
+                SyntaxError::UnmatchedClosingChar { got, column } => {
+                    println!("ERR: nothing to close with {} in col {}", got, column);
+                    syntax_error_symbols.increment(got);
+                    false
+                },
+                SyntaxError::UnknownSymbol { got, column } => {
+                    println!("ERR: unknown symbol {} in col {}", got, column);
+                    false
                 }
             }
         };
     }
 
-    let syntax_errors_score =
-        syntax_error_symbols.parenthesis * ILLEGAL_PARENTHESIS_SCORE +
-            syntax_error_symbols.square_bracket * ILLEGAL_SQUARE_BRACKET_SCORE +
-            syntax_error_symbols.curly_bracket * ILLEGAL_CURLY_BRACKET_SCORE +
-            syntax_error_symbols.angle_bracket * ILLEGAL_ANGLE_BRACKET_SCORE;
-
-    println!("Score for the first part: {}", syntax_errors_score);
+    println!("Score for the first part: {}", syntax_error_symbols.score());
 }
 
 fn part2(lines: &[LineChecker]) {
@@ -197,15 +277,16 @@ fn part2(lines: &[LineChecker]) {
                 // Complete lines are already complete, no need to autocomplete
                 Ok(_) => None,
                 Err(e) => match e {
-                    // Corrupted lines are thrown out
-                    SyntaxError::UnexpectedClosingChar { .. } => None,
                     SyntaxError::IncompleteLine { stack } => Some((line, stack)),
+                    // Corrupted lines are thrown out
+                    _ => None,
                 }
             }
         })
         .map(|(line, stack)| line.complete(Some(&stack)))
         .map(|l| l.unwrap())
         .map(|(complete_line, score)| {
+            let score = score.unwrap_or_else(|| panic!("The completion score of {} doesn't fit in 64 bits", complete_line));
             println!("Completed line: {} -> {}", complete_line, score);
             score
         })
@@ -221,4 +302,56 @@ fn part2(lines: &[LineChecker]) {
         completed_lines_scores.len(),
         completion_score
     )
-}
\ No newline at end of file
+}
+
+// Not a part of the puzzle: show how the chunks of each line nest into each other
+fn dump_trees(content: &str, as_json: bool) {
+    let trees = content
+        .lines()
+        .map(ChunkTree::parse)
+        .collect::<Vec<_>>();
+
+    let parsed_trees = trees.iter().filter_map(|tree| tree.as_ref().ok());
+    let max_depth = parsed_trees.clone().map(ChunkTree::max_depth).max().unwrap_or(0);
+    let chunk_count = parsed_trees.clone().map(ChunkTree::chunk_count).sum::<usize>();
+    let corrupted_count = trees.iter().filter(|tree| tree.is_err()).count();
+
+    if as_json {
+        let lines = trees
+            .iter()
+            .enumerate()
+            .map(|(line_number, tree)| match tree {
+                Ok(tree) => Value::object(vec![("line", line_number.into()), ("tree", tree.to_json())]),
+                Err(_) => Value::object(vec![("line", line_number.into()), ("tree", Value::Null)]),
+            })
+            .collect();
+
+        let dump = Value::object(vec![
+            ("max_depth", max_depth.into()),
+            ("chunk_count", chunk_count.into()),
+            ("corrupted_count", corrupted_count.into()),
+            ("lines", Value::Array(lines)),
+        ]);
+
+        println!("{}", dump);
+        return;
+    }
+
+    for (line_number, tree) in trees.iter().enumerate() {
+        match tree {
+            Ok(tree) => {
+                println!("Line {}: depth {}, {} chunks{}", line_number, tree.max_depth(), tree.chunk_count(),
+                         if tree.is_complete() { "" } else { ", incomplete" });
+                print!("{}", tree);
+            },
+            Err(_) => println!("Line {}: corrupted, no tree", line_number),
+        }
+    }
+
+    println!(
+        "Max depth: {}, total chunks: {}, corrupted lines skipped: {}",
+        max_depth,
+        chunk_count,
+        corrupted_count
+    );
+}
@@ -0,0 +1,407 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Add;
+
+use crate::json::Value;
+use crate::{LineChecker, SyntaxError, SyntaxViolationsContainer};
+
+// Order in which the bracket kinds appear in the report, same as in the puzzle
+const CLOSING_SYMBOLS: [char; 4] = [')', ']', '}', '>'];
+
+pub struct CorruptedLine<'line> {
+    pub line_number: usize,
+    pub line: &'line str,
+    // None when the closing symbol had nothing to close
+    pub expected: Option<char>,
+    pub got: char,
+    pub score: u64,
+}
+
+pub struct IncompleteLine<'line> {
+    pub line_number: usize,
+    pub line: &'line str,
+    pub closing_sequence: String,
+    pub score: Score,
+}
+
+// Completion scores grow five times with each missing symbol and soon don't fit in 64 bits. A longer
+// closing sequence always has a larger score, so the scores that are too large are indeed larger than
+// all the others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Score {
+    Points(u64),
+    TooLarge,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Distribution<T> {
+    pub min: T,
+    // Same pick as the puzzle: the element in the middle after sorting, the upper one for even counts
+    pub median: T,
+    pub max: T,
+}
+
+// Summary of a whole file: what went wrong, which bracket kinds are to blame and on which lines
+pub struct Report<'line> {
+    pub valid_lines: Vec<usize>,
+    pub corrupted_lines: Vec<CorruptedLine<'line>>,
+    pub incomplete_lines: Vec<IncompleteLine<'line>>,
+    // Lines containing something else than brackets
+    pub invalid_lines: Vec<usize>,
+    corrupted_symbols: SyntaxViolationsContainer,
+    completion_symbols: SyntaxViolationsContainer,
+}
+
+impl From<Option<u64>> for Score {
+    fn from(score: Option<u64>) -> Self {
+        score.map_or(Score::TooLarge, Score::Points)
+    }
+}
+
+impl Add for Score {
+    type Output = Score;
+
+    fn add(self, other: Score) -> Score {
+        match (self, other) {
+            (Score::Points(a), Score::Points(b)) => a.checked_add(b).into(),
+            _ => Score::TooLarge,
+        }
+    }
+}
+
+impl Display for Score {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Score::Points(points) => points.fmt(f),
+            Score::TooLarge => f.pad("too large"),
+        }
+    }
+}
+
+impl From<Score> for Value {
+    fn from(score: Score) -> Self {
+        match score {
+            Score::Points(points) => points.into(),
+            Score::TooLarge => Value::Null,
+        }
+    }
+}
+
+impl<T: Ord + Copy + Into<Value>> Distribution<T> {
+    pub fn of(scores: &[T]) -> Option<Self> {
+        let mut scores = scores.to_vec();
+        scores.sort_unstable();
+
+        Some(Self {
+            min: *scores.first()?,
+            median: scores[scores.len() / 2],
+            max: *scores.last()?,
+        })
+    }
+
+    fn to_json(&self) -> Value {
+        Value::object(vec![
+            ("min", self.min.into()),
+            ("median", self.median.into()),
+            ("max", self.max.into()),
+        ])
+    }
+}
+
+impl<'line> Report<'line> {
+    pub fn new(content: &'line str) -> Self {
+        let mut report = Self {
+            valid_lines: Vec::new(),
+            corrupted_lines: Vec::new(),
+            incomplete_lines: Vec::new(),
+            invalid_lines: Vec::new(),
+            corrupted_symbols: SyntaxViolationsContainer::new(),
+            completion_symbols: SyntaxViolationsContainer::new(),
+        };
+
+        for (line_number, line) in content.lines().enumerate() {
+            let checker = LineChecker::new(line);
+
+            let (expected, got) = match checker.check() {
+                Ok(_) => {
+                    report.valid_lines.push(line_number);
+                    continue;
+                },
+                Err(SyntaxError::IncompleteLine { stack }) => {
+                    let (completed_line, score) = checker.complete(Some(&stack)).expect("Incomplete lines can be completed");
+                    let closing_sequence = completed_line[line.len()..].to_string();
+
+                    for symbol in closing_sequence.chars() {
+                        report.completion_symbols.increment(symbol);
+                    }
+
+                    report.incomplete_lines.push(IncompleteLine { line_number, line, closing_sequence, score: score.into() });
+                    continue;
+                },
+                Err(SyntaxError::UnknownSymbol { .. }) => {
+                    report.invalid_lines.push(line_number);
+                    continue;
+                },
+                Err(SyntaxError::UnexpectedClosingChar { expected, got, .. }) => (Some(expected), got),
+                Err(SyntaxError::UnmatchedClosingChar { got, .. }) => (None, got),
+            };
+
+            report.corrupted_symbols.increment(got);
+            report.corrupted_lines.push(CorruptedLine {
+                line_number,
+                line,
+                expected,
+                got,
+                score: LineChecker::illegal_score_for(got) as u64,
+            });
+        }
+
+        report
+    }
+
+    // Part 1 answer
+    pub fn corruption_score(&self) -> u64 {
+        self.corrupted_symbols.score()
+    }
+
+    // Part 2 answer, if there is anything to complete
+    pub fn completion_score(&self) -> Option<Score> {
+        self.completion_distribution().map(|distribution| distribution.median)
+    }
+
+    pub fn corruption_distribution(&self) -> Option<Distribution<u64>> {
+        Distribution::of(&self.corrupted_lines.iter().map(|l| l.score).collect::<Vec<_>>())
+    }
+
+    pub fn completion_distribution(&self) -> Option<Distribution<Score>> {
+        Distribution::of(&self.incomplete_lines.iter().map(|l| l.score).collect::<Vec<_>>())
+    }
+
+    // Count and total score of each kind of illegal closing symbol
+    pub fn corruption_breakdown(&self) -> Vec<(char, u32, u64)> {
+        CLOSING_SYMBOLS
+            .iter()
+            .map(|&symbol| {
+                let count = self.corrupted_symbols.get(symbol);
+                (symbol, count, count as u64 * LineChecker::illegal_score_for(symbol) as u64)
+            })
+            .collect()
+    }
+
+    // Count of each kind of closing symbol added by the completion, along with its share of the
+    // completion scores. A completion score is a number in base 5 where each added symbol is one
+    // digit, so the share of a symbol is its digit value times the weight of its position.
+    pub fn completion_breakdown(&self) -> Vec<(char, u32, Score)> {
+        CLOSING_SYMBOLS
+            .iter()
+            .map(|&symbol| {
+                let contribution = self.incomplete_lines
+                    .iter()
+                    .flat_map(|line| {
+                        let sequence_length = line.closing_sequence.len() as u32;
+                        line.closing_sequence
+                            .chars()
+                            .enumerate()
+                            .filter(move |(_, c)| *c == symbol)
+                            .map(move |(idx, c)| 5u64
+                                .checked_pow(sequence_length - 1 - idx as u32)
+                                .and_then(|weight| weight.checked_mul(LineChecker::completion_score_for(c)))
+                                .into())
+                    })
+                    .fold(Score::Points(0), Score::add);
+
+                (symbol, self.completion_symbols.get(symbol), contribution)
+            })
+            .collect()
+    }
+
+    pub fn to_json(&self) -> Value {
+        fn breakdown_to_json<T: Into<Value>>(breakdown: Vec<(char, u32, T)>) -> Value {
+            Value::Array(
+                breakdown
+                    .into_iter()
+                    .map(|(symbol, count, score)| Value::object(vec![
+                        ("symbol", symbol.to_string().into()),
+                        ("count", count.into()),
+                        ("score", score.into()),
+                    ]))
+                    .collect()
+            )
+        }
+
+        fn distribution_to_json<T: Ord + Copy + Into<Value>>(distribution: Option<Distribution<T>>) -> Value {
+            distribution
+                .map(|d| d.to_json())
+                .unwrap_or(Value::Null)
+        }
+
+        let corrupted_lines = self.corrupted_lines
+            .iter()
+            .map(|l| Value::object(vec![
+                ("line_number", l.line_number.into()),
+                ("line", l.line.into()),
+                ("expected", l.expected.map(|c| c.to_string().into()).unwrap_or(Value::Null)),
+                ("got", l.got.to_string().into()),
+                ("score", l.score.into()),
+            ]))
+            .collect();
+
+        let incomplete_lines = self.incomplete_lines
+            .iter()
+            .map(|l| Value::object(vec![
+                ("line_number", l.line_number.into()),
+                ("line", l.line.into()),
+                ("closing_sequence", l.closing_sequence.as_str().into()),
+                ("score", l.score.into()),
+            ]))
+            .collect();
+
+        Value::object(vec![
+            ("valid_lines", Value::Array(self.valid_lines.iter().map(|&n| n.into()).collect())),
+            ("invalid_lines", Value::Array(self.invalid_lines.iter().map(|&n| n.into()).collect())),
+            ("corrupted", Value::object(vec![
+                ("score", self.corruption_score().into()),
+                ("breakdown", breakdown_to_json(self.corruption_breakdown())),
+                ("distribution", distribution_to_json(self.corruption_distribution())),
+                ("lines", Value::Array(corrupted_lines)),
+            ])),
+            ("incomplete", Value::object(vec![
+                ("score", self.completion_score().map(Value::from).unwrap_or(Value::Null)),
+                ("breakdown", breakdown_to_json(self.completion_breakdown())),
+                ("distribution", distribution_to_json(self.completion_distribution())),
+                ("lines", Value::Array(incomplete_lines)),
+            ])),
+        ])
+    }
+}
+
+impl<'line> Display for Report<'line> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        fn fmt_distribution<T: Display>(f: &mut Formatter<'_>, distribution: Option<Distribution<T>>) -> std::fmt::Result {
+            match distribution {
+                Some(d) => writeln!(f, "  Scores: min {}, median {}, max {}", d.min, d.median, d.max),
+                None => writeln!(f, "  Scores: none"),
+            }
+        }
+
+        writeln!(f, "Corrupted lines: {}, score {}", self.corrupted_lines.len(), self.corruption_score())?;
+        for (symbol, count, score) in self.corruption_breakdown() {
+            writeln!(f, "  {} count {:>4} score {:>10}", symbol, count, score)?;
+        }
+        fmt_distribution(f, self.corruption_distribution())?;
+        for l in &self.corrupted_lines {
+            match l.expected {
+                Some(expected) => writeln!(f, "  Line {}: {} -> expected {}, got {} ({})", l.line_number, l.line, expected, l.got, l.score)?,
+                None => writeln!(f, "  Line {}: {} -> nothing to close with {} ({})", l.line_number, l.line, l.got, l.score)?,
+            }
+        }
+
+        match self.completion_score() {
+            Some(score) => writeln!(f, "Incomplete lines: {}, score {}", self.incomplete_lines.len(), score)?,
+            None => writeln!(f, "Incomplete lines: 0")?,
+        }
+        for (symbol, count, score) in self.completion_breakdown() {
+            writeln!(f, "  {} count {:>4} score {:>10}", symbol, count, score)?;
+        }
+        fmt_distribution(f, self.completion_distribution())?;
+        for l in &self.incomplete_lines {
+            writeln!(f, "  Line {}: {} -> {} ({})", l.line_number, l.line, l.closing_sequence, l.score)?;
+        }
+
+        writeln!(f, "Valid lines: {}", self.valid_lines.len())?;
+        if !self.invalid_lines.is_empty() {
+            writeln!(f, "Lines with unknown symbols: {:?}", self.invalid_lines)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::{Distribution, Report, Score};
+
+    fn get_example() -> String {
+        fs::read_to_string("../files/day10/example.txt").expect("Files does not exist")
+    }
+
+    #[test]
+    fn example_scores(){
+        let content = get_example();
+        let report = Report::new(&content);
+
+        assert_eq!(report.corruption_score(), 26397);
+        assert_eq!(report.completion_score(), Some(Score::Points(288957)));
+        assert_eq!(report.corrupted_lines.len(), 5);
+        assert_eq!(report.incomplete_lines.len(), 5);
+    }
+
+    #[test]
+    fn corruption_breakdown_adds_up(){
+        let content = get_example();
+        let report = Report::new(&content);
+
+        assert_eq!(report.corruption_breakdown(), vec![(')', 2, 6), (']', 1, 57), ('}', 1, 1197), ('>', 1, 25137)]);
+        assert_eq!(report.corruption_distribution(), Some(Distribution { min: 3, median: 57, max: 25137 }));
+    }
+
+    #[test]
+    fn completion_breakdown_adds_up(){
+        let content = get_example();
+        let report = Report::new(&content);
+
+        let contributions = report.completion_breakdown().iter().fold(Score::Points(0), |sum, (_, _, score)| sum + *score);
+        let scores = report.incomplete_lines.iter().fold(Score::Points(0), |sum, l| sum + l.score);
+        assert_eq!(contributions, scores);
+
+        let single_line = Report::new("<{([");
+        assert_eq!(single_line.completion_breakdown(), vec![
+            (')', 1, Score::Points(25)),
+            (']', 1, Score::Points(250)),
+            ('}', 1, Score::Points(15)),
+            ('>', 1, Score::Points(4)),
+        ]);
+    }
+
+    #[test]
+    fn large_scores_stay_exact_in_json(){
+        let line = "[".repeat(25);
+        let report = Report::new(&line);
+        let score = report.to_json().get("incomplete").and_then(|incomplete| incomplete.get("score")).cloned();
+
+        assert_eq!(report.completion_score(), Some(Score::Points(149011611938476562)));
+        assert_eq!(score.map(|score| score.to_string()), Some("149011611938476562".to_string()));
+    }
+
+    #[test]
+    fn scores_past_64_bits(){
+        // 28 missing symbols still fit, not 29
+        let content = ["(".repeat(28), "(".repeat(30), "(".repeat(29)].join("\n");
+        let report = Report::new(&content);
+
+        assert_eq!(report.incomplete_lines[0].score, Score::Points(9313225746154785156));
+        assert_eq!(report.incomplete_lines[1].score, Score::TooLarge);
+        assert_eq!(report.completion_distribution(), Some(Distribution {
+            min: Score::Points(9313225746154785156),
+            median: Score::TooLarge,
+            max: Score::TooLarge,
+        }));
+        assert_eq!(report.completion_breakdown()[0], (')', 87, Score::TooLarge));
+
+        let incomplete = report.to_json().get("incomplete").cloned().unwrap();
+        assert_eq!(incomplete.get("score"), Some(&crate::json::Value::Null));
+        assert!(report.to_string().contains("Incomplete lines: 3, score too large"));
+    }
+
+    #[test]
+    fn corruption_score_past_32_bits(){
+        let content = vec![">"; 200_000].join("\n");
+        assert_eq!(Report::new(&content).corruption_score(), 200_000 * 25137);
+    }
+
+    #[test]
+    fn distribution_of_nothing(){
+        assert_eq!(Distribution::<u64>::of(&[]), None);
+    }
+}
@@ -1,9 +1,17 @@
 use std::env::args;
+use std::fmt::{Display, Formatter};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::num::ParseIntError;
 use std::str::FromStr;
 
+use submarine::{Steering, Submarine, SurfacePolicy};
+
+mod submarine;
+mod trajectory;
+
+const USAGE: &str = "USAGE: day2 <input file> [--surface allow|clamp|error] [--trace|--export csv|svg [--part 1|2]]";
+
 #[derive(Debug, Copy, Clone)]
 enum Direction {
     Forward(u32),
@@ -12,6 +20,7 @@ enum Direction {
 }
 
 #[derive(Debug)]
+#[allow(dead_code)] // Fields are only read through Debug when unwrapping
 enum DirectionError {
     NotEnoughItems,
     IntError(ParseIntError),
@@ -46,9 +55,49 @@ impl FromStr for Direction {
     }
 }
 
+// Same format as the input
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Direction::Forward(movement) => write!(f, "forward {}", movement),
+            Direction::Up(movement) => write!(f, "up {}", movement),
+            Direction::Down(movement) => write!(f, "down {}", movement),
+        }
+    }
+}
+
 fn main(){
-    let input_filename = args().nth(1).expect("USAGE: day2 <input file>");
-    let file = File::open(&input_filename).unwrap_or_else(|_| panic!("Can't open file {}", input_filename));
+    let arguments = args().skip(1).collect::<Vec<_>>();
+
+    let mut input_filename = None;
+    // Going above the surface doesn't make much sense, but that's what the puzzle would do
+    let mut policy = SurfacePolicy::AllowNegative;
+    let mut trace = false;
+    let mut export_format = None;
+    // The dive profile of part 2 is the more interesting one
+    let mut steering = Steering::Aim;
+
+    let mut arguments_iter = arguments.iter();
+    while let Some(argument) = arguments_iter.next() {
+        if argument == "--surface" {
+            policy = arguments_iter.next().and_then(|p| SurfacePolicy::from_str(p).ok()).expect(USAGE);
+        } else if argument == "--trace" {
+            trace = true;
+        } else if argument == "--export" {
+            export_format = Some(arguments_iter.next().filter(|f| *f == "csv" || *f == "svg").expect(USAGE));
+        } else if argument == "--part" {
+            steering = match arguments_iter.next().map(String::as_str) {
+                Some("1") => Steering::Direct,
+                Some("2") => Steering::Aim,
+                _ => panic!("{}", USAGE),
+            };
+        } else {
+            input_filename = Some(argument);
+        }
+    }
+
+    let input_filename = input_filename.expect(USAGE);
+    let file = File::open(input_filename).unwrap_or_else(|_| panic!("Can't open file {}", input_filename));
     let file = BufReader::new(file);
 
     let lines = file.lines()
@@ -57,36 +106,73 @@ fn main(){
         .map(Result::unwrap)
         .collect::<Vec<Direction>>();
 
-    part1(&lines);
-    part2(&lines);
+    if let Some(export_format) = export_format {
+        export_trajectory(&lines, steering, policy, export_format);
+        return;
+    }
+
+    if trace {
+        trace_course(&lines, "Part 1", Steering::Direct, policy);
+        trace_course(&lines, "Part 2", Steering::Aim, policy);
+    }
+
+    part1(&lines, policy);
+    part2(&lines, policy);
+}
+
+fn trace_course(lines: &[Direction], part: &str, steering: Steering, policy: SurfacePolicy) {
+    for (step, direction) in Submarine::new(steering, policy).course(lines).zip(lines) {
+        match step {
+            Ok(state) => println!(
+                "{} step {} ({}): Horizontal: {}, Depth: {}, Aim: {}",
+                part, state.step, direction, state.horizontal, state.depth, state.aim
+            ),
+            Err(e) => println!("{} step {} ({}): refused, the depth would be {}", part, e.command, direction, e.depth),
+        }
+    }
+}
+
+// Prints the state after each command, stopping at the first refused command if any
+fn export_trajectory(lines: &[Direction], steering: Steering, policy: SurfacePolicy, format: &str) {
+    let submarine = Submarine::new(steering, policy);
+    let mut states = vec![submarine.state()];
+
+    for step in submarine.course(lines) {
+        match step {
+            Ok(state) => states.push(state),
+            Err(e) => eprintln!("Command {} would take the submarine to {}, the course stops there", e.command, e.depth),
+        }
+    }
+
+    if format == "csv" {
+        print!("{}", trajectory::to_csv(&states, lines));
+    } else {
+        print!("{}", trajectory::to_svg(&states));
+    }
 }
 
 // Part 1: calculate horizontal and vertical movement
-fn part1(lines: &[Direction]){
-    let (horizontal, depth) = lines
-        .iter()
-        .fold((0, 0), |(cur_hor, cur_depth), direction|{
-            match *direction {
-                Direction::Forward(movement) => (cur_hor + movement, cur_depth),
-                Direction::Up(movement) => (cur_hor, cur_depth - movement),
-                Direction::Down(movement) => (cur_hor, cur_depth + movement)
-            }
-        });
-
-    println!("Part 1: Horizontal: {}, Depth: {}, Result: {}", horizontal, depth, horizontal * depth);
+fn part1(lines: &[Direction], policy: SurfacePolicy){
+    let mut submarine = Submarine::new(Steering::Direct, policy);
+
+    match submarine.apply_all(lines) {
+        Ok(()) => println!(
+            "Part 1: Horizontal: {}, Depth: {}, Result: {}",
+            submarine.horizontal, submarine.depth, submarine.result()
+        ),
+        Err(e) => println!("Part 1: Command {} would take the submarine to {}", e.command, e.depth),
+    }
 }
 
 // Part 2: calculate aim in addition of vertical and horizontal movement
-fn part2(lines: &[Direction]){
-    let (horizontal, depth, aim) = lines
-        .iter()
-        .fold((0, 0, 0), |(cur_hor, cur_depth, cur_aim), direction| {
-            match *direction {
-                Direction::Forward(movement) => (cur_hor + movement, cur_depth + movement * cur_aim, cur_aim),
-                Direction::Up(movement) => (cur_hor, cur_depth, cur_aim - movement),
-                Direction::Down(movement) => (cur_hor, cur_depth, cur_aim + movement),
-            }
-        });
-
-    println!("Part 2: Horizontal: {}, Depth: {}, Aim: {} Result: {}", horizontal, depth, aim, horizontal * depth);
-}
\ No newline at end of file
+fn part2(lines: &[Direction], policy: SurfacePolicy){
+    let mut submarine = Submarine::new(Steering::Aim, policy);
+
+    match submarine.apply_all(lines) {
+        Ok(()) => println!(
+            "Part 2: Horizontal: {}, Depth: {}, Aim: {} Result: {}",
+            submarine.horizontal, submarine.depth, submarine.aim, submarine.result()
+        ),
+        Err(e) => println!("Part 2: Command {} would take the submarine to {}", e.command, e.depth),
+    }
+}
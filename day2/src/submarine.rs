@@ -0,0 +1,257 @@
+use std::str::FromStr;
+
+use crate::Direction;
+
+// How the commands move the submarine
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Steering {
+    // Part 1: up and down change the depth
+    Direct,
+    // Part 2: up and down change the aim, moving forward dives along it
+    Aim,
+}
+
+// What happens when a command would take the submarine above the surface
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SurfacePolicy {
+    // The depth simply goes negative
+    AllowNegative,
+    // The submarine stops at the surface
+    Clamp,
+    // The command is refused
+    Error,
+}
+
+// The command, starting at 1, that would have taken the submarine above the surface
+#[derive(Debug, PartialEq)]
+pub struct SurfaceError {
+    pub command: usize,
+    pub depth: i64,
+}
+
+impl FromStr for SurfacePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "allow" => SurfacePolicy::AllowNegative,
+            "clamp" => SurfacePolicy::Clamp,
+            "error" => SurfacePolicy::Error,
+            _ => return Err(s.to_string()),
+        })
+    }
+}
+
+// Where the submarine is after some commands
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct State {
+    // Number of commands applied so far
+    pub step: usize,
+    pub horizontal: i64,
+    pub depth: i64,
+    pub aim: i64,
+}
+
+// The state after each command, stopping after the first refused command
+pub struct Course<'a> {
+    submarine: Submarine,
+    directions: std::slice::Iter<'a, Direction>,
+    refused: bool,
+}
+
+impl Iterator for Course<'_> {
+    type Item = Result<State, SurfaceError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.refused {
+            return None;
+        }
+
+        let direction = self.directions.next()?;
+        let result = self.submarine.apply(*direction).map(|()| self.submarine.state());
+        self.refused = result.is_err();
+
+        Some(result)
+    }
+}
+
+// The aim can be negative whatever the policy, the submarine then goes up when moving forward
+#[derive(Debug, Clone, PartialEq)]
+pub struct Submarine {
+    pub horizontal: i64,
+    pub depth: i64,
+    pub aim: i64,
+    steering: Steering,
+    policy: SurfacePolicy,
+    command_count: usize,
+}
+
+impl Submarine {
+    pub fn new(steering: Steering, policy: SurfacePolicy) -> Self {
+        Self {
+            horizontal: 0,
+            depth: 0,
+            aim: 0,
+            steering,
+            policy,
+            command_count: 0,
+        }
+    }
+
+    // A refused command leaves the submarine where it was
+    pub fn apply(&mut self, direction: Direction) -> Result<(), SurfaceError> {
+        self.command_count += 1;
+
+        let (mut horizontal, mut depth, mut aim) = (self.horizontal, self.depth, self.aim);
+
+        match (self.steering, direction) {
+            (Steering::Direct, Direction::Forward(movement)) => horizontal += movement as i64,
+            (Steering::Direct, Direction::Up(movement)) => depth -= movement as i64,
+            (Steering::Direct, Direction::Down(movement)) => depth += movement as i64,
+            (Steering::Aim, Direction::Forward(movement)) => {
+                horizontal += movement as i64;
+                depth += movement as i64 * aim;
+            },
+            (Steering::Aim, Direction::Up(movement)) => aim -= movement as i64,
+            (Steering::Aim, Direction::Down(movement)) => aim += movement as i64,
+        }
+
+        match self.policy {
+            SurfacePolicy::Clamp => depth = depth.max(0),
+            SurfacePolicy::Error if depth < 0 => return Err(SurfaceError { command: self.command_count, depth }),
+            _ => {},
+        }
+
+        self.horizontal = horizontal;
+        self.depth = depth;
+        self.aim = aim;
+
+        Ok(())
+    }
+
+    pub fn state(&self) -> State {
+        State {
+            step: self.command_count,
+            horizontal: self.horizontal,
+            depth: self.depth,
+            aim: self.aim,
+        }
+    }
+
+    // Goes through the commands one at a time
+    pub fn course(self, directions: &[Direction]) -> Course<'_> {
+        Course { submarine: self, directions: directions.iter(), refused: false }
+    }
+
+    pub fn apply_all(&mut self, directions: &[Direction]) -> Result<(), SurfaceError> {
+        directions.iter().try_for_each(|direction| self.apply(*direction))
+    }
+
+    // Horizontal position times depth, the answer of both parts
+    pub fn result(&self) -> i64 {
+        self.horizontal * self.depth
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::str::FromStr;
+
+    use super::{State, Steering, Submarine, SurfaceError, SurfacePolicy};
+    use crate::Direction;
+
+    fn get_directions(filename: &str) -> Vec<Direction> {
+        fs::read_to_string(filename)
+            .unwrap()
+            .lines()
+            .map(|line| Direction::from_str(line).unwrap())
+            .collect()
+    }
+
+    macro_rules! course_test {
+        ($name:ident, $filename:literal, $steering:ident -> ($horizontal:literal, $depth:literal)) => {
+            #[test]
+            fn $name(){
+                let mut submarine = Submarine::new(Steering::$steering, SurfacePolicy::AllowNegative);
+                submarine.apply_all(&get_directions($filename)).unwrap();
+                assert_eq!((submarine.horizontal, submarine.depth), ($horizontal, $depth));
+            }
+        };
+    }
+
+    course_test!(example_part1,     "../files/day2/example.txt", Direct -> (15, 10));
+    course_test!(example_part2,     "../files/day2/example.txt", Aim -> (15, 60));
+    course_test!(input_part1,       "../files/day2/input.txt", Direct -> (1868, 1090));
+    course_test!(input_part2,       "../files/day2/input.txt", Aim -> (1868, 1078987));
+
+    fn surface_course(steering: Steering, policy: SurfacePolicy) -> (Submarine, Result<(), SurfaceError>) {
+        let mut submarine = Submarine::new(steering, policy);
+        let result = submarine.apply_all(&[Direction::Down(2), Direction::Forward(3), Direction::Up(5), Direction::Forward(2)]);
+        (submarine, result)
+    }
+
+    #[test]
+    fn above_the_surface_allowed(){
+        let (submarine, result) = surface_course(Steering::Direct, SurfacePolicy::AllowNegative);
+        assert_eq!(result, Ok(()));
+        assert_eq!(submarine.depth, -3);
+        assert_eq!(submarine.result(), -15);
+    }
+
+    #[test]
+    fn clamped_at_the_surface(){
+        let (submarine, result) = surface_course(Steering::Direct, SurfacePolicy::Clamp);
+        assert_eq!(result, Ok(()));
+        assert_eq!(submarine.depth, 0);
+    }
+
+    #[test]
+    fn surface_error(){
+        let (submarine, result) = surface_course(Steering::Direct, SurfacePolicy::Error);
+        assert_eq!(result, Err(SurfaceError { command: 3, depth: -3 }));
+        // The command was refused
+        assert_eq!(submarine.depth, 2);
+    }
+
+    #[test]
+    fn negative_aim(){
+        // The aim goes to -3, moving forward by 2 brings the submarine back to the surface
+        let (mut submarine, result) = surface_course(Steering::Aim, SurfacePolicy::Error);
+        assert_eq!(result, Ok(()));
+        assert_eq!((submarine.aim, submarine.depth), (-3, 0));
+
+        assert_eq!(submarine.apply(Direction::Forward(1)), Err(SurfaceError { command: 5, depth: -3 }));
+        assert_eq!(submarine.horizontal, 5);
+    }
+
+    #[test]
+    fn state_after_each_command(){
+        let states = Submarine::new(Steering::Aim, SurfacePolicy::AllowNegative)
+            .course(&get_directions("../files/day2/example.txt"))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(states.len(), 6);
+        assert_eq!(states[1], State { step: 2, horizontal: 5, depth: 0, aim: 5 });
+        assert_eq!(states[2], State { step: 3, horizontal: 13, depth: 40, aim: 5 });
+        assert_eq!(states[5], State { step: 6, horizontal: 15, depth: 60, aim: 10 });
+    }
+
+    #[test]
+    fn course_stops_at_the_refused_command(){
+        let directions = [Direction::Down(1), Direction::Up(2), Direction::Down(5)];
+        let course = Submarine::new(Steering::Direct, SurfacePolicy::Error).course(&directions).collect::<Vec<_>>();
+
+        assert_eq!(course, vec![
+            Ok(State { step: 1, horizontal: 0, depth: 1, aim: 0 }),
+            Err(SurfaceError { command: 2, depth: -1 }),
+        ]);
+    }
+
+    #[test]
+    fn policies(){
+        assert_eq!(SurfacePolicy::from_str("clamp"), Ok(SurfacePolicy::Clamp));
+        assert_eq!(SurfacePolicy::from_str("float"), Err("float".to_string()));
+    }
+}
@@ -0,0 +1,116 @@
+use crate::submarine::State;
+use crate::Direction;
+
+const SVG_WIDTH: f64 = 800.0;
+const SVG_HEIGHT: f64 = 400.0;
+const SVG_MARGIN: f64 = 40.0;
+
+// One line per state, with the command that led to it. The first state is the start of the course,
+// before any command.
+pub fn to_csv(states: &[State], directions: &[Direction]) -> String {
+    let mut csv = String::from("step,command,horizontal,depth,aim\n");
+
+    for state in states {
+        let command = match state.step {
+            0 => String::new(),
+            step => directions[step - 1].to_string(),
+        };
+
+        csv += &format!("{},{},{},{},{}\n", state.step, command, state.horizontal, state.depth, state.aim);
+    }
+
+    csv
+}
+
+// Dive profile: the horizontal position from left to right and the depth from top to bottom, with the
+// surface drawn as a line when it is in view
+pub fn to_svg(states: &[State]) -> String {
+    let min_horizontal = states.iter().map(|state| state.horizontal).min().unwrap_or(0).min(0);
+    let max_horizontal = states.iter().map(|state| state.horizontal).max().unwrap_or(0).max(0);
+    let min_depth = states.iter().map(|state| state.depth).min().unwrap_or(0).min(0);
+    let max_depth = states.iter().map(|state| state.depth).max().unwrap_or(0).max(0);
+
+    // Both ranges are stretched separately, the depths are usually way larger than the distances
+    let scale = |value: i64, min: i64, max: i64, size: f64| match max - min {
+        0 => SVG_MARGIN,
+        range => SVG_MARGIN + (value - min) as f64 / range as f64 * (size - 2.0 * SVG_MARGIN),
+    };
+    let x = |horizontal: i64| scale(horizontal, min_horizontal, max_horizontal, SVG_WIDTH);
+    let y = |depth: i64| scale(depth, min_depth, max_depth, SVG_HEIGHT);
+
+    let points = states
+        .iter()
+        .map(|state| format!("{:.1},{:.1}", x(state.horizontal), y(state.depth)))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+        SVG_WIDTH, SVG_HEIGHT, SVG_WIDTH, SVG_HEIGHT
+    );
+    svg += "\n";
+    svg += &format!(
+        r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="steelblue" stroke-dasharray="4"/>"#,
+        x(min_horizontal), y(0), x(max_horizontal), y(0)
+    );
+    svg += "\n";
+    svg += &format!(r#"<polyline points="{}" fill="none" stroke="black"/>"#, points);
+    svg += "\n";
+    svg += &format!(
+        r#"<text x="{:.1}" y="{:.1}" font-size="12">horizontal {} to {}, depth {} to {}</text>"#,
+        SVG_MARGIN, SVG_MARGIN / 2.0, min_horizontal, max_horizontal, min_depth, max_depth
+    );
+    svg += "\n</svg>\n";
+
+    svg
+}
+
+#[cfg(test)]
+mod test {
+    use super::{to_csv, to_svg};
+    use crate::submarine::State;
+    use crate::Direction;
+
+    fn states() -> Vec<State> {
+        vec![
+            State { step: 0, horizontal: 0, depth: 0, aim: 0 },
+            State { step: 1, horizontal: 0, depth: 0, aim: 5 },
+            State { step: 2, horizontal: 10, depth: 50, aim: 5 },
+        ]
+    }
+
+    #[test]
+    fn csv(){
+        assert_eq!(to_csv(&states(), &[Direction::Down(5), Direction::Forward(10)]), concat!(
+            "step,command,horizontal,depth,aim\n",
+            "0,,0,0,0\n",
+            "1,down 5,0,0,5\n",
+            "2,forward 10,10,50,5\n",
+        ));
+    }
+
+    #[test]
+    fn svg_fits_the_course(){
+        let svg = to_svg(&states());
+
+        assert!(svg.starts_with("<svg "));
+        // The deepest point is at the bottom right corner, inside the margins
+        assert!(svg.contains(r#"<polyline points="40.0,40.0 40.0,40.0 760.0,360.0""#));
+        // The surface is the top of the course
+        assert!(svg.contains(r#"<line x1="40.0" y1="40.0" x2="760.0" y2="40.0""#));
+        assert!(svg.contains("horizontal 0 to 10, depth 0 to 50"));
+    }
+
+    #[test]
+    fn svg_above_the_surface(){
+        let svg = to_svg(&[
+            State { step: 0, horizontal: 0, depth: 0, aim: 0 },
+            State { step: 1, horizontal: 0, depth: -10, aim: 0 },
+            State { step: 2, horizontal: 4, depth: 30, aim: 0 },
+        ]);
+
+        // The surface is a quarter of the way down
+        assert!(svg.contains(r#"y1="120.0""#));
+        assert!(svg.contains("depth -10 to 30"));
+    }
+}
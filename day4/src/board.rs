@@ -1,140 +1,269 @@
-pub const COLUMN_COUNT_PER_GRID: usize = 5;
-pub const LINE_COUNT_PER_GRID: usize = 5;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use crate::win_rule::WinRule;
+
+const ANSI_MARKED: &str = "\x1b[1;7m";
+const ANSI_RESET: &str = "\x1b[0m";
 
 #[derive(Debug, Clone)]
 pub struct Board {
-    // One number that have been marked will be turned into None
     // Lines are placed one directly after the other like so:
     // [ <line 1 items> <line 2 items> ... ]
-    numbers: Vec<Option<u32>>,
+    numbers: Vec<u32>,
+    // One bit per number, in the same order, set once the number has been marked
+    marked: Vec<u64>,
+    line_count: usize,
+    column_count: usize,
+    // Where each number sits in `numbers`, so marking doesn't have to look through the whole grid
+    cell_by_number: HashMap<u32, usize>,
+    // Every pattern of cells that wins once all marked, taken from the win rules: the board wins as soon as
+    // one of them is complete. With the rule of the puzzle, these are the lines followed by the columns.
+    // For each pattern, how many of its cells are still unmarked, and for each cell the patterns it is a part of.
+    unmarked_per_pattern: Vec<usize>,
+    patterns_per_cell: Vec<Vec<usize>>,
+    won: bool,
 }
 
-pub struct BoardLineIterator<'board> {
-    board: &'board Board,
-    current_line: usize,
-}
+impl Board {
+    pub fn new(numbers: &[u32], column_count: usize) -> Self {
+        assert!(column_count > 0, "A board needs at least one column");
+        assert!(
+            !numbers.is_empty() && numbers.len().is_multiple_of(column_count),
+            "{} numbers can't be arranged in lines of {} columns", numbers.len(), column_count
+        );
 
-pub struct BoardColumnIterator<'board> {
-    board: &'board Board,
-    current_column: usize
-}
+        let mut cell_by_number = HashMap::with_capacity(numbers.len());
+        for (cell, number) in numbers.iter().enumerate() {
+            if cell_by_number.insert(*number, cell).is_some() {
+                panic!("The number {} appears more than once in the grid", number);
+            }
+        }
 
-impl<'board> Iterator for BoardLineIterator<'board> {
-    type Item = &'board [Option<u32>];
+        let mut board = Self {
+            numbers: numbers.to_vec(),
+            marked: vec![0; numbers.len().div_ceil(64)],
+            line_count: numbers.len() / column_count,
+            column_count,
+            cell_by_number,
+            unmarked_per_pattern: vec![],
+            patterns_per_cell: vec![],
+            won: false,
+        };
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let line = self.board.get_line(self.current_line);
-        self.current_line += 1;
-        line
+        board.set_win_rules(&[WinRule::LinesAndColumns]);
+        board
     }
-}
 
-impl<'board> Iterator for BoardColumnIterator<'board> {
-    type Item = Vec<Option<u32>>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let column = self.board.get_column(self.current_column);
-        self.current_column += 1;
-        column
+    pub fn with_win_rules(mut self, win_rules: &[WinRule]) -> Self {
+        assert!(!win_rules.is_empty(), "A board without any rule can't be won");
+        self.set_win_rules(win_rules);
+        self
     }
-}
 
-impl Board {
-    pub fn new(numbers: &[u32]) -> Self {
-        Self {
-            numbers: numbers.iter().map(|n| Some(*n)).collect()
-        }
-    }
+    fn set_win_rules(&mut self, win_rules: &[WinRule]) {
+        self.unmarked_per_pattern.clear();
+        self.patterns_per_cell = vec![vec![]; self.numbers.len()];
 
-    pub fn mark(&mut self, number_to_mark: u32) {
-        self.numbers = self.numbers
+        let patterns = win_rules
             .iter()
-            .copied()
-            .map(|grid_number| {
-                match grid_number {
-                    None => None,
-                    Some(number) if number == number_to_mark => None,
-                    Some(number) => Some(number)
-                }
-            })
-            .collect()
-    }
+            .flat_map(|rule| rule.patterns(self.line_count, self.column_count))
+            .collect::<Vec<_>>();
 
-    pub fn is_win(&self) -> bool {
+        for (pattern_idx, pattern) in patterns.into_iter().enumerate() {
+            let unmarked = pattern.iter().filter(|cell| !self.is_cell_marked(**cell)).count();
+            self.unmarked_per_pattern.push(unmarked);
 
-        for line in self.lines() {
-            if line == [None; COLUMN_COUNT_PER_GRID] {
-                return true;
+            for cell in pattern {
+                self.patterns_per_cell[cell].push(pattern_idx);
             }
         }
 
-        for column in self.columns() {
-            let column = column.as_slice();
-            if column == [None; LINE_COUNT_PER_GRID] {
-                return true;
-            }
-        }
+        self.won = self.unmarked_per_pattern.contains(&0);
+    }
 
-        false
+    pub fn line_count(&self) -> usize {
+        self.line_count
     }
 
-    pub fn lines(&self) -> BoardLineIterator {
-        BoardLineIterator {
-            board: self,
-            current_line: 0
-        }
+    pub fn column_count(&self) -> usize {
+        self.column_count
+    }
+
+    pub fn get(&self, line: usize, column: usize) -> u32 {
+        self.numbers[self.cell(line, column)]
+    }
+
+    pub fn is_marked(&self, line: usize, column: usize) -> bool {
+        self.is_cell_marked(self.cell(line, column))
+    }
+
+    fn cell(&self, line: usize, column: usize) -> usize {
+        assert!(line < self.line_count && column < self.column_count, "({}, {}) is outside of the grid", line, column);
+        line * self.column_count + column
+    }
+
+    fn is_cell_marked(&self, cell: usize) -> bool {
+        self.marked[cell / 64] & (1 << (cell % 64)) != 0
+    }
+
+    pub fn contains(&self, number: u32) -> bool {
+        self.cell_by_number.contains_key(&number)
     }
 
-    pub fn columns(&self) -> BoardColumnIterator {
-        BoardColumnIterator {
-            board: self,
-            current_column: 0
+    // Returns true when this number is the one that makes the board win
+    pub fn mark(&mut self, number_to_mark: u32) -> bool {
+        let cell = match self.cell_by_number.get(&number_to_mark) {
+            Some(cell) => *cell,
+            None => return false,
+        };
+
+        // Already marked, nothing changes
+        if self.is_cell_marked(cell) {
+            return false;
+        }
+
+        self.marked[cell / 64] |= 1 << (cell % 64);
+
+        let mut completes_pattern = false;
+        for pattern_idx in &self.patterns_per_cell[cell] {
+            self.unmarked_per_pattern[*pattern_idx] -= 1;
+            completes_pattern |= self.unmarked_per_pattern[*pattern_idx] == 0;
         }
+
+        let is_new_win = completes_pattern && !self.won;
+        self.won |= completes_pattern;
+        is_new_win
+    }
+
+    pub fn is_win(&self) -> bool {
+        self.won
     }
 
+    // Sum of the numbers that are not marked
     pub fn calculate_score(&self) -> u32 {
         self.numbers
             .iter()
-            .filter_map(|n| *n)
+            .enumerate()
+            .filter(|(cell, _)| !self.is_cell_marked(*cell))
+            .map(|(_, number)| number)
             .sum()
     }
+}
+
+// Marked numbers are put between brackets: `[14]`. With the alternate flag (`{:#}`), they are highlighted
+// with ANSI escape codes instead.
+impl Display for Board {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let width = self.numbers.iter().map(|n| n.to_string().len()).max().unwrap_or(1);
+
+        for line in 0..self.line_count {
+            for column in 0..self.column_count {
+                if column > 0 {
+                    f.write_str(" ")?;
+                }
+
+                let number = self.get(line, column);
+                match (self.is_marked(line, column), f.alternate()) {
+                    (true, true) => write!(f, "{} {:>width$} {}", ANSI_MARKED, number, ANSI_RESET, width = width)?,
+                    (true, false) => write!(f, "[{:>width$}]", number, width = width)?,
+                    (false, _) => write!(f, " {:>width$} ", number, width = width)?,
+                }
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
 
-    pub fn get_line(&self, n: usize) -> Option<&[Option<u32>]> {
-        // Since each line is place one after the other in the numbers vector, grabbing one line is
-        // trivial: determine the interval start on the nth line column zero and the interval end
-        // at the end of the line (a.k.a the number of columns in the line).
+#[cfg(test)]
+mod test {
+    use super::Board;
+    use crate::win_rule::WinRule;
 
-        // Formula: The nth line we want to fetch * number of columns per line
-        let interval_start = n * COLUMN_COUNT_PER_GRID;
+    // 2 lines of 3 columns:
+    // 1 2 3
+    // 4 5 6
+    fn get_board() -> Board {
+        Board::new(&[1, 2, 3, 4, 5, 6], 3)
+    }
 
-        // Formula: The nth line we want to fetch * number of columns per line + number of columns per line
-        let interval_end = n * COLUMN_COUNT_PER_GRID + COLUMN_COUNT_PER_GRID;
+    #[test]
+    fn unknown_numbers_are_ignored(){
+        let mut board = get_board();
+        board.mark(6);
+        assert!(!board.mark(42));
+        assert_eq!(board.calculate_score(), 1 + 2 + 3 + 4 + 5);
+    }
 
-        self.numbers.get(interval_start..interval_end)
+    #[test]
+    fn column_of_two_wins(){
+        let mut board = get_board();
+        assert!(!board.mark(2));
+        assert!(!board.is_win());
+        assert!(board.mark(5));
+        assert!(board.is_win());
+        assert_eq!(board.calculate_score(), 1 + 3 + 4 + 6);
     }
 
-    pub fn get_column(&self, n: usize) -> Option<Vec<Option<u32>>> {
-        let mut column = Vec::with_capacity(COLUMN_COUNT_PER_GRID);
+    #[test]
+    fn only_the_first_win_is_reported(){
+        let mut board = get_board();
+        board.mark(1);
+        assert!(board.mark(4));
+        assert!(!board.mark(4));
+        assert!(!board.mark(2));
+        assert!(!board.mark(5));
+        assert!(board.is_win());
+    }
 
-        // Getting one column gets a bit trickier: we want the nth column on each line
-        for current_line in 0..LINE_COUNT_PER_GRID {
-            // Formula: current line we're looking at * the number of columns per line + the nth column we're
-            // looking for in the line
+    #[test]
+    fn changing_rules_keeps_the_marks(){
+        let mut board = get_board();
+        board.mark(1);
+        board.mark(2);
+        let mut board = board.with_win_rules(&[WinRule::Blackout]);
+        board.mark(3);
+        assert!(!board.is_win());
+        board.mark(4);
+        board.mark(5);
+        assert!(board.mark(6));
+    }
 
-            // `current line we're looking at * the number of columns per line` allows us to point ourselves
-            // on a specific line and `the nth column we're looking for in the line` fetches the the nth column.
-            let idx = current_line * COLUMN_COUNT_PER_GRID + n;
+    #[test]
+    fn numbers_are_kept_once_marked(){
+        let mut board = get_board();
+        board.mark(5);
+        assert_eq!(board.get(1, 1), 5);
+        assert!(board.is_marked(1, 1));
+        assert!(!board.is_marked(0, 1));
+    }
 
-            // In case the column does not exist or is incomplete, independently of whenever the whole column
-            // has been marked or not, we return None. Otherwise, put the number we find there on the vector
-            let number_at_that_line = match self.numbers.get(idx) {
-                Some(n) => n,
-                None => return None,
-            };
+    #[test]
+    fn display_plain(){
+        let mut board = Board::new(&[1, 22, 3, 14], 2);
+        board.mark(22);
+        assert_eq!(board.to_string(), "  1  [22]\n  3   14 \n");
+    }
 
-            column.push(*number_at_that_line);
-        }
+    #[test]
+    fn display_ansi(){
+        let mut board = Board::new(&[1, 2], 2);
+        board.mark(1);
+        assert_eq!(format!("{:#}", board), "\x1b[1;7m 1 \x1b[0m  2 \n");
+    }
+
+    #[test]
+    #[should_panic]
+    fn reject_non_rectangular_numbers(){
+        Board::new(&[1, 2, 3, 4], 3);
+    }
 
-        Some(column)
+    #[test]
+    #[should_panic]
+    fn reject_duplicate_numbers(){
+        Board::new(&[1, 2, 1], 3);
     }
 }
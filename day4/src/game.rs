@@ -0,0 +1,210 @@
+use crate::board::Board;
+
+// One board completing one of its winning patterns
+#[derive(Debug, Clone, PartialEq)]
+pub struct Win {
+    pub board: usize,
+    // Sum of the numbers left unmarked on the board
+    pub board_score: u32,
+    // Board score times the number that made it win
+    pub final_score: u32,
+}
+
+// Everything that happened when drawing one number
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    // Starts at 1 for the first number drawn
+    pub turn: usize,
+    pub number: u32,
+    // Boards still in the game that had the number
+    pub marked_boards: Vec<usize>,
+    // Several boards can win with the same number
+    pub winners: Vec<Win>,
+}
+
+// Draws numbers over all the boards. Once a board has won, it leaves the game: it is not marked anymore
+// and its score stays the one it had when winning.
+pub struct Game {
+    boards: Vec<Board>,
+    events: Vec<Event>,
+}
+
+impl Game {
+    pub fn new(boards: &[Board]) -> Self {
+        Self {
+            boards: boards.to_vec(),
+            events: Vec::new(),
+        }
+    }
+
+    // Draw all the numbers, stopping early once every board has won
+    pub fn play(boards: &[Board], numbers: &[u32]) -> Self {
+        let mut game = Self::new(boards);
+
+        for number in numbers {
+            if game.is_over() {
+                break;
+            }
+
+            game.draw(*number);
+        }
+
+        game
+    }
+
+    pub fn draw(&mut self, number: u32) -> &Event {
+        let mut marked_boards = Vec::new();
+        let mut winners = Vec::new();
+
+        for (idx, board) in self.boards.iter_mut().enumerate() {
+            if board.is_win() || !board.contains(number) {
+                continue;
+            }
+
+            marked_boards.push(idx);
+
+            if board.mark(number) {
+                let board_score = board.calculate_score();
+                winners.push(Win { board: idx, board_score, final_score: board_score * number });
+            }
+        }
+
+        self.events.push(Event {
+            turn: self.events.len() + 1,
+            number,
+            marked_boards,
+            winners,
+        });
+
+        self.events.last().unwrap()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.boards.iter().all(Board::is_win)
+    }
+
+    pub fn board(&self, idx: usize) -> &Board {
+        &self.boards[idx]
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    // Every win along with the event it happened in, in the order they happened. Boards winning with
+    // the same number are in the order of the input.
+    pub fn win_order(&self) -> impl Iterator<Item = (&Event, &Win)> + '_ {
+        self.events
+            .iter()
+            .flat_map(|event| event.winners.iter().map(move |win| (event, win)))
+    }
+
+    pub fn first_winner(&self) -> Option<(&Event, &Win)> {
+        self.win_order().next()
+    }
+
+    pub fn last_winner(&self) -> Option<(&Event, &Win)> {
+        self.win_order().last()
+    }
+
+    pub fn to_json(&self) -> String {
+        let join = |items: Vec<String>| items.join(",");
+
+        let events = self.events
+            .iter()
+            .map(|event| format!(
+                r#"{{"turn":{},"number":{},"marked_boards":[{}],"winners":[{}]}}"#,
+                event.turn,
+                event.number,
+                join(event.marked_boards.iter().map(ToString::to_string).collect()),
+                join(event.winners.iter().map(Win::to_json).collect()),
+            ))
+            .collect();
+
+        let win_order = self.win_order()
+            .map(|(_, win)| win.board.to_string())
+            .collect();
+
+        format!(
+            r#"{{"board_count":{},"events":[{}],"win_order":[{}]}}"#,
+            self.boards.len(),
+            join(events),
+            join(win_order),
+        )
+    }
+}
+
+impl Win {
+    fn to_json(&self) -> String {
+        format!(
+            r#"{{"board":{},"board_score":{},"final_score":{}}}"#,
+            self.board, self.board_score, self.final_score
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Game, Win};
+    use crate::board::Board;
+
+    // 1 2    3 4    1 3
+    // 3 4    5 6    5 6
+    fn get_boards() -> Vec<Board> {
+        vec![
+            Board::new(&[1, 2, 3, 4], 2),
+            Board::new(&[3, 4, 5, 6], 2),
+            Board::new(&[1, 3, 5, 6], 2),
+        ]
+    }
+
+    #[test]
+    fn events_of_a_game(){
+        let game = Game::play(&get_boards(), &[3, 1, 6, 5, 2, 4]);
+        let events = game.events();
+
+        assert_eq!(events[0].marked_boards, vec![0, 1, 2]);
+        assert!(events[0].winners.is_empty());
+
+        // Board 0 (column 1 3) and board 2 (line 1 3) win together
+        assert_eq!(events[1].winners, vec![
+            Win { board: 0, board_score: 6, final_score: 6 },
+            Win { board: 2, board_score: 11, final_score: 11 },
+        ]);
+
+        // Won boards are not marked anymore
+        assert_eq!(events[2].marked_boards, vec![1]);
+        assert!(events[2].winners.is_empty());
+        assert_eq!(events[3].winners, vec![Win { board: 1, board_score: 4, final_score: 20 }]);
+
+        // Nothing left to play
+        assert_eq!(events.len(), 4);
+        assert!(game.is_over());
+    }
+
+    #[test]
+    fn first_and_last_winners(){
+        let game = Game::play(&get_boards(), &[3, 1, 6, 5, 2, 4]);
+
+        let win_order = game.win_order().map(|(_, win)| win.board).collect::<Vec<_>>();
+        assert_eq!(win_order, vec![0, 2, 1]);
+        assert_eq!(game.first_winner().map(|(event, win)| (event.number, win.board)), Some((1, 0)));
+        assert_eq!(game.last_winner().map(|(event, win)| (event.number, win.board)), Some((5, 1)));
+    }
+
+    #[test]
+    fn nobody_wins(){
+        let game = Game::play(&get_boards(), &[1, 6]);
+        assert!(game.first_winner().is_none());
+        assert!(!game.is_over());
+    }
+
+    #[test]
+    fn json_export(){
+        let game = Game::play(&get_boards(), &[4, 2]);
+        assert_eq!(
+            game.to_json(),
+            r#"{"board_count":3,"events":[{"turn":1,"number":4,"marked_boards":[0,1],"winners":[]},{"turn":2,"number":2,"marked_boards":[0],"winners":[{"board":0,"board_score":4,"final_score":8}]}],"win_order":[0]}"#
+        );
+    }
+}
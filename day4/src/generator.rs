@@ -0,0 +1,264 @@
+use std::collections::HashSet;
+
+use crate::board::Board;
+use crate::rng::Rng;
+use crate::win_rule::WinRule;
+
+// Numbers of a board made up by us, one line after the other
+#[derive(Debug, Clone)]
+pub struct GeneratedBoard {
+    pub numbers: Vec<u32>,
+    pub column_count: usize,
+    // Turn (starting at 1) at which the board wins with the draws it was built for, if it wins at all
+    pub winning_turn: Option<usize>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum GeneratorError {
+    // Not enough different numbers to fill every cell of the grid
+    NotEnoughNumbers { needed: usize, available: usize },
+    // None of the rules can be satisfied on a grid of that size
+    NoWinningPattern,
+}
+
+impl GeneratedBoard {
+    fn new(numbers: Vec<u32>, column_count: usize, win_rules: &[WinRule], draws: &[u32]) -> Self {
+        let board = Board::new(&numbers, column_count).with_win_rules(win_rules);
+        let winning_turn = winning_turn(&board, draws);
+
+        Self {
+            numbers,
+            column_count,
+            winning_turn,
+        }
+    }
+
+    // Same format as the grids in the input files
+    pub fn to_grid_string(&self) -> String {
+        self.numbers
+            .chunks(self.column_count)
+            .map(|line| line.iter().map(|n| format!("{:>2}", n)).collect::<Vec<_>>().join(" "))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+// Play the draws on a copy of the board until it wins
+pub fn winning_turn(board: &Board, draws: &[u32]) -> Option<usize> {
+    let mut board = board.clone();
+
+    for (idx, number) in draws.iter().enumerate() {
+        board.mark(*number);
+
+        if board.is_win() {
+            return Some(idx + 1);
+        }
+    }
+
+    None
+}
+
+// Random board with unique numbers taken from 0..=max_number
+pub fn random_board(
+    rng: &mut Rng,
+    line_count: usize,
+    column_count: usize,
+    max_number: u32,
+    win_rules: &[WinRule],
+    draws: &[u32],
+) -> Result<GeneratedBoard, GeneratorError> {
+    let cell_count = line_count * column_count;
+    let available = max_number as usize + 1;
+
+    if available < cell_count {
+        return Err(GeneratorError::NotEnoughNumbers { needed: cell_count, available });
+    }
+
+    let mut pool = (0..=max_number).collect::<Vec<_>>();
+    rng.shuffle(&mut pool);
+    pool.truncate(cell_count);
+
+    Ok(GeneratedBoard::new(pool, column_count, win_rules, draws))
+}
+
+// The smallest winning pattern filled with the first numbers drawn. No board can win earlier: winning
+// needs at least that many numbers to be drawn.
+pub fn fastest_board(
+    line_count: usize,
+    column_count: usize,
+    win_rules: &[WinRule],
+    draws: &[u32],
+) -> Result<GeneratedBoard, GeneratorError> {
+    let cell_count = line_count * column_count;
+    let unique = unique_draws(draws);
+
+    let smallest_pattern = win_rules
+        .iter()
+        .flat_map(|rule| rule.patterns(line_count, column_count))
+        .min_by_key(Vec::len)
+        .ok_or(GeneratorError::NoWinningPattern)?;
+
+    if unique.len() < smallest_pattern.len() {
+        return Err(GeneratorError::NotEnoughNumbers { needed: smallest_pattern.len(), available: unique.len() });
+    }
+
+    // The rest of the grid gets the numbers drawn last, then numbers that are never drawn
+    let never_drawn = (0..).filter(|n| !unique.contains(n));
+    let mut filler = unique[smallest_pattern.len()..]
+        .iter()
+        .rev()
+        .copied()
+        .chain(never_drawn);
+
+    let mut numbers = vec![None; cell_count];
+    for (cell, number) in smallest_pattern.iter().zip(unique.iter()) {
+        numbers[*cell] = Some(*number);
+    }
+
+    let numbers = numbers
+        .into_iter()
+        .map(|n| n.unwrap_or_else(|| filler.next().unwrap()))
+        .collect();
+
+    // The winning turn counts every draw, repeated ones included
+    Ok(GeneratedBoard::new(numbers, column_count, win_rules, draws))
+}
+
+// A board only made of drawn numbers that wins as late as possible. Every winning pattern needs one
+// number drawn late: cells get the last numbers drawn, picking each time the cell that is in the most
+// patterns still without one. The remaining cells get the first numbers drawn.
+// Picking the cells greedily is optimal for lines and columns, other rules may in some cases end up a few
+// turns short of the latest possible win.
+pub fn slowest_board(
+    line_count: usize,
+    column_count: usize,
+    win_rules: &[WinRule],
+    draws: &[u32],
+) -> Result<GeneratedBoard, GeneratorError> {
+    let cell_count = line_count * column_count;
+    let unique = unique_draws(draws);
+
+    if unique.len() < cell_count {
+        return Err(GeneratorError::NotEnoughNumbers { needed: cell_count, available: unique.len() });
+    }
+
+    let patterns = win_rules
+        .iter()
+        .flat_map(|rule| rule.patterns(line_count, column_count))
+        .collect::<Vec<_>>();
+
+    if patterns.is_empty() {
+        return Err(GeneratorError::NoWinningPattern);
+    }
+
+    let mut uncovered_patterns = (0..patterns.len()).collect::<HashSet<_>>();
+    let mut late_numbers = unique.iter().rev();
+    let mut numbers = vec![None; cell_count];
+
+    while !uncovered_patterns.is_empty() {
+        let cell = (0..cell_count)
+            .filter(|cell| numbers[*cell].is_none())
+            .max_by_key(|cell| {
+                let covered = uncovered_patterns.iter().filter(|p| patterns[**p].contains(cell)).count();
+                // Ties go to the first cell
+                (covered, std::cmp::Reverse(*cell))
+            })
+            .expect("Every pattern has at least one cell");
+
+        numbers[cell] = late_numbers.next().copied();
+        uncovered_patterns.retain(|p| !patterns[*p].contains(&cell));
+    }
+
+    let mut early_numbers = unique.iter();
+    let numbers = numbers
+        .into_iter()
+        .map(|n| n.unwrap_or_else(|| *early_numbers.next().unwrap()))
+        .collect();
+
+    // The winning turn counts every draw, repeated ones included
+    Ok(GeneratedBoard::new(numbers, column_count, win_rules, draws))
+}
+
+// Drawing a number twice doesn't change anything the second time
+fn unique_draws(draws: &[u32]) -> Vec<u32> {
+    let mut seen = HashSet::new();
+    draws.iter().copied().filter(|n| seen.insert(*n)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::{fastest_board, random_board, slowest_board, winning_turn, GeneratorError};
+    use crate::board::Board;
+    use crate::rng::Rng;
+    use crate::win_rule::WinRule;
+
+    fn draws() -> Vec<u32> {
+        (0..100).rev().collect()
+    }
+
+    #[test]
+    fn random_boards_have_unique_numbers(){
+        let mut rng = Rng::new(1);
+        let generated = random_board(&mut rng, 5, 5, 99, &[WinRule::LinesAndColumns], &draws()).unwrap();
+
+        let unique = generated.numbers.iter().collect::<HashSet<_>>();
+        assert_eq!(unique.len(), 25);
+        assert!(generated.numbers.iter().all(|n| *n <= 99));
+    }
+
+    #[test]
+    fn random_boards_are_reproducible(){
+        let first = random_board(&mut Rng::new(3), 5, 5, 99, &[WinRule::LinesAndColumns], &draws()).unwrap();
+        let second = random_board(&mut Rng::new(3), 5, 5, 99, &[WinRule::LinesAndColumns], &draws()).unwrap();
+        assert_eq!(first.numbers, second.numbers);
+    }
+
+    #[test]
+    fn not_enough_numbers(){
+        let result = random_board(&mut Rng::new(0), 5, 5, 9, &[WinRule::LinesAndColumns], &draws());
+        assert_eq!(result.err(), Some(GeneratorError::NotEnoughNumbers { needed: 25, available: 10 }));
+    }
+
+    macro_rules! search_test {
+        ($name:ident, $search:ident, ($lines:literal x $columns:literal), [$($rule:expr),*] -> $turn:literal) => {
+            #[test]
+            fn $name(){
+                let generated = $search($lines, $columns, &[$($rule),*], &draws()).unwrap();
+                assert_eq!(generated.winning_turn, Some($turn));
+
+                // Double check through a board built from scratch
+                let board = Board::new(&generated.numbers, $columns).with_win_rules(&[$($rule),*]);
+                assert_eq!(winning_turn(&board, &draws()), Some($turn));
+            }
+        };
+    }
+
+    search_test!(fastest_lines,             fastest_board, (5 x 5), [WinRule::LinesAndColumns] -> 5);
+    search_test!(fastest_rectangle,         fastest_board, (2 x 4), [WinRule::LinesAndColumns] -> 2);
+    search_test!(fastest_corners,           fastest_board, (5 x 5), [WinRule::FourCorners] -> 4);
+    search_test!(fastest_blackout,          fastest_board, (3 x 3), [WinRule::Blackout] -> 9);
+
+    // The board wins once the fifth number from the end is drawn: 100 - 5 + 1
+    search_test!(slowest_lines,             slowest_board, (5 x 5), [WinRule::LinesAndColumns] -> 96);
+    search_test!(slowest_rectangle,         slowest_board, (2 x 4), [WinRule::LinesAndColumns] -> 97);
+    search_test!(slowest_with_diagonals,    slowest_board, (5 x 5), [WinRule::LinesAndColumns, WinRule::Diagonals] -> 96);
+    search_test!(slowest_corners,           slowest_board, (5 x 5), [WinRule::FourCorners] -> 100);
+
+    #[test]
+    fn turns_count_repeated_draws(){
+        // 9 9 8 8 7 7 ... 0 0
+        let draws = (0..10).rev().flat_map(|n| [n, n]).collect::<Vec<u32>>();
+
+        let fastest = fastest_board(2, 2, &[WinRule::LinesAndColumns], &draws).unwrap();
+        // 9 and 8 make a line, 8 is first drawn on turn 3
+        assert_eq!(fastest.winning_turn, Some(3));
+        assert_eq!(draws[fastest.winning_turn.unwrap() - 1], 8);
+
+        let slowest = slowest_board(2, 2, &[WinRule::LinesAndColumns], &draws).unwrap();
+        // Lines and columns all need 0 or 1, 1 is first drawn on turn 17
+        assert_eq!(slowest.winning_turn, Some(17));
+        assert_eq!(draws[slowest.winning_turn.unwrap() - 1], 1);
+    }
+}
@@ -1,26 +1,69 @@
 use std::env::args;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{stdout, BufRead, BufReader, IsTerminal};
 use std::str::FromStr;
+use std::thread;
 
 use board::Board;
-use board::LINE_COUNT_PER_GRID;
+use game::Game;
+use rng::Rng;
+use win_rule::WinRule;
 
 mod board;
+mod game;
+mod generator;
+mod rng;
+mod simulation;
+mod win_rule;
+
+const USAGE: &str = "USAGE: day4 <input file> [--rule lines|diagonals|corners|blackout|mask:<x./.x>]... \
+[--events|--json|--generate <count> [--seed <seed>]|--search|--simulate <trials> [--seed <seed>] [--threads <count>]]";
 
 fn main() {
-    // Read the file and parse the contents of it so we can extract the numbers and the boards
-    let filename = args().nth(1).expect("USAGE: day4 <input file>");
-    let file = File::open(&filename).expect("File does not exist");
-    let file = BufReader::new(file);
+    // Each `--rule` adds one way of winning, the boards are won with full lines and columns by default
+    let arguments = args().skip(1).collect::<Vec<_>>();
+    let mut filename = None;
+    let mut win_rules = vec![];
+    let mut print_event_log = false;
+    let mut export_json = false;
+    let mut generate_count = None;
+    let mut seed = 0;
+    let mut search = false;
+    let mut simulation_trials = None;
+    let mut thread_count = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
 
-    let mut board_file_content = file
-        .split(0x0A)
-        .map(Result::unwrap)
-        .map(String::from_utf8)
-        .map(Result::unwrap)
-        .filter(|line| !line.is_empty())
-        .collect::<Vec<String>>();
+    let mut arguments_iter = arguments.iter();
+    while let Some(argument) = arguments_iter.next() {
+        if argument == "--rule" {
+            let rule = arguments_iter.next().expect(USAGE);
+            win_rules.push(WinRule::from_str(rule).unwrap_or_else(|e| panic!("Invalid rule {}: {:?}", rule, e)));
+        } else if argument == "--events" {
+            print_event_log = true;
+        } else if argument == "--json" {
+            export_json = true;
+        } else if argument == "--generate" {
+            generate_count = Some(arguments_iter.next().and_then(|n| usize::from_str(n).ok()).expect(USAGE));
+        } else if argument == "--seed" {
+            seed = arguments_iter.next().and_then(|n| u64::from_str(n).ok()).expect(USAGE);
+        } else if argument == "--search" {
+            search = true;
+        } else if argument == "--simulate" {
+            simulation_trials = Some(arguments_iter.next().and_then(|n| usize::from_str(n).ok()).expect(USAGE));
+        } else if argument == "--threads" {
+            thread_count = arguments_iter.next().and_then(|n| usize::from_str(n).ok()).expect(USAGE);
+        } else {
+            filename = Some(argument);
+        }
+    }
+
+    if win_rules.is_empty() {
+        win_rules.push(WinRule::LinesAndColumns);
+    }
+
+    // Read the file and parse the contents of it so we can extract the numbers and the boards
+    let filename = filename.expect(USAGE);
+    let file = File::open(filename).expect("File does not exist");
+    let mut board_file_content = read_lines(BufReader::new(file));
 
     // Extract the random numbers
     let numbers = board_file_content
@@ -32,95 +75,226 @@ fn main() {
         .collect::<Vec<u32>>();
 
     // Extract each game grid from the file
-    let boards = {
-        let mut boards = vec![];
-
-        for board in board_file_content.chunks(LINE_COUNT_PER_GRID) {
-            let board = board
-                .iter()
-                .map(|s| s.split(' '))
-                .flatten()
-                .filter(|n| !n.is_empty())
+    let boards = parse_boards(&board_file_content)
+        .into_iter()
+        .map(|board| board.with_win_rules(&win_rules))
+        .collect::<Vec<_>>();
+
+    for rule in &win_rules {
+        if let WinRule::Mask(mask) = rule {
+            if let Some(idx) = boards.iter().position(|board| !mask.fits(board)) {
+                panic!("The mask {:?} doesn't have the same size as grid {}", mask, idx);
+            }
+        }
+    }
+
+    // Made up grids have the same size as the first grid of the file
+    let (line_count, column_count) = boards
+        .first()
+        .map(|board| (board.line_count(), board.column_count()))
+        .unwrap_or((5, 5));
+
+    if let Some(count) = generate_count {
+        generate_boards(count, seed, line_count, column_count, &win_rules, &numbers);
+        return;
+    }
+
+    if search {
+        search_boards(line_count, column_count, &win_rules, &numbers);
+        return;
+    }
+
+    if let Some(trials) = simulation_trials {
+        print_simulation(&simulation::simulate(&boards, &numbers, trials, seed, thread_count));
+        return;
+    }
+
+    let game = Game::play(&boards, &numbers);
+
+    if export_json {
+        println!("{}", game.to_json());
+        return;
+    }
+
+    if print_event_log {
+        print_events(&game);
+    }
+
+    part1(&game);
+    part2(&game);
+}
+
+// Trimmed, which also gets rid of the carriage returns of Windows line endings
+fn read_lines<R: BufRead>(input: R) -> Vec<String> {
+    input
+        .split(0x0A)
+        .map(Result::unwrap)
+        .map(String::from_utf8)
+        .map(Result::unwrap)
+        .map(|line| line.trim().to_string())
+        .collect()
+}
+
+// Grids are separated by blank lines, their size is given by the number of lines and columns between
+// those separators
+fn parse_boards(lines: &[String]) -> Vec<Board> {
+    let mut boards = vec![];
+
+    for grid in lines.split(|line| line.is_empty()).filter(|grid| !grid.is_empty()) {
+        let grid = grid
+            .iter()
+            .map(|line| line
+                .split_whitespace()
                 .map(u32::from_str)
                 .map(Result::unwrap)
-                .collect::<Vec<_>>();
+                .collect::<Vec<_>>())
+            .collect::<Vec<_>>();
 
-            boards.push(Board::new(&board));
+        let column_count = grid[0].len();
+        if let Some(line) = grid.iter().find(|line| line.len() != column_count) {
+            panic!("Grid {} is not rectangular: expected {} columns, got a line with {}", boards.len(), column_count, line.len());
         }
 
-        boards
-    };
+        boards.push(Board::new(&grid.concat(), column_count));
+    }
 
-    part1(&numbers, &boards);
-    part2(&numbers, &boards);
+    boards
 }
 
-fn part1(numbers: &[u32], grids: &[Board]) {
-    // Grab a copy of the boards for ourselves since we will modify it through the marking of each number
-    let mut grids = grids.to_vec();
-    let mut winning_board = None;
-    let mut last_called = None;
-
-    // Mark each number in the grids
-    'outer: for number in numbers {
-        let number = *number;
-
-        for (idx, grid) in grids.iter_mut().enumerate() {
-            grid.mark(number);
-            if grid.is_win() {
-                winning_board = Some(idx);
-                last_called = Some(number);
-                break 'outer;
+fn part1(game: &Game) {
+    match game.first_winner() {
+        Some((event, win)) => println!(
+            "Winning grid at {} with grid score of {}, last called number {}, final score: {}",
+            win.board, win.board_score, event.number, win.final_score
+        ),
+        None => println!("No winning grid :("),
+    }
+}
+
+fn part2(game: &Game) {
+    if !game.is_over() {
+        println!("Some grids never win with these numbers");
+        return;
+    }
+
+    let (event, win) = game.last_winner().expect("All the grids won, so there is a last one");
+    println!(
+        "Last winning grid at {} with score of {}, last called number {}, final score: {}",
+        win.board, win.board_score, event.number, win.final_score
+    );
+}
+
+fn print_events(game: &Game) {
+    // Highlight the marked numbers with colors, unless the output ends up in a file
+    let use_ansi = stdout().is_terminal();
+
+    for event in game.events() {
+        println!("Turn {}: drew {}, marked on grids {:?}", event.turn, event.number, event.marked_boards);
+
+        for win in &event.winners {
+            println!("    Grid {} wins with grid score of {}, final score: {}", win.board, win.board_score, win.final_score);
+
+            let board = game.board(win.board);
+            if use_ansi {
+                print!("{:#}", board);
+            } else {
+                print!("{}", board);
             }
         }
     }
 
-    // We are out of the for loops, check if we effectively have a winning board
-    if let Some(winning_index) = winning_board {
-        let grid = grids.get(winning_index).expect("Somehow, the winning board is not in the array");
-        let last_called = last_called.expect("How do you win if you haven't even called a number ?");
+    let win_order = game.win_order().map(|(_, win)| win.board).collect::<Vec<_>>();
+    println!("Win order: {:?}", win_order);
+}
+
+// Prints a whole input file: the same numbers to draw, followed by random grids
+fn generate_boards(count: usize, seed: u64, line_count: usize, column_count: usize, win_rules: &[WinRule], numbers: &[u32]) {
+    let mut rng = Rng::new(seed);
+    let max_number = numbers.iter().copied().max().unwrap_or(99);
 
-        let score = grid.calculate_score();
-        println!("Winning grid at {} with grid score of {}, last called number {}, final score: {}", winning_index, score, last_called, last_called * score);
-    } else {
-        println!("No winning grid :(")
+    println!("{}", numbers.iter().map(ToString::to_string).collect::<Vec<_>>().join(","));
+
+    for _ in 0..count {
+        let generated = generator::random_board(&mut rng, line_count, column_count, max_number, win_rules, numbers)
+            .unwrap_or_else(|e| panic!("Can't generate a grid: {:?}", e));
+
+        println!();
+        println!("{}", generated.to_grid_string());
     }
 }
 
-fn part2(numbers: &[u32], grids: &[Board]) {
-    let mut grids = grids.to_vec();
-
-    for number in numbers {
-        let number = *number;
+fn search_boards(line_count: usize, column_count: usize, win_rules: &[WinRule], numbers: &[u32]) {
+    let searches = [
+        ("Fastest", generator::fastest_board(line_count, column_count, win_rules, numbers)),
+        ("Slowest", generator::slowest_board(line_count, column_count, win_rules, numbers)),
+    ];
 
-        for grid in grids.iter_mut() {
-            grid.mark(number)
+    for (name, result) in searches {
+        match result {
+            Ok(generated) => {
+                match generated.winning_turn {
+                    Some(turn) => println!("{} grid, wins at turn {} when drawing {}:", name, turn, numbers[turn - 1]),
+                    None => println!("{} grid, never wins:", name),
+                }
+                println!("{}", generated.to_grid_string());
+            },
+            Err(e) => println!("{} grid: can't build one, {:?}", name, e),
         }
+    }
+}
 
-        let winning_grids_count = grids
-            .iter()
-            .filter(|grid| grid.is_win())
-            .count();
+fn print_simulation(report: &simulation::SimulationReport) {
+    println!("{} trials with shuffled numbers", report.trials);
 
-        if winning_grids_count ==  grids.len() - 1 {
-            break;
+    for (idx, outcome) in report.boards.iter().enumerate() {
+        match outcome.mean_winning_turn {
+            Some(mean_turn) => println!(
+                "Grid {}: wins first {:.2}% of the time, wins on turn {:.2} on average",
+                idx, outcome.first_win_probability * 100.0, mean_turn
+            ),
+            None => println!("Grid {}: never wins", idx),
         }
     }
+}
 
-    let non_winning_grid = grids.iter_mut().find(|g| !g.is_win()).unwrap();
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
 
-    // Play the numbers until the grid is won
-    let mut last_called = None;
-    for number in numbers {
-        non_winning_grid.mark(*number);
-        last_called = Some(*number);
+    use super::{parse_boards, read_lines};
 
-        if non_winning_grid.is_win() {
-            break;
-        }
+    fn get_boards(content: &str) -> Vec<(usize, usize)> {
+        parse_boards(&read_lines(Cursor::new(content)))
+            .iter()
+            .map(|board| (board.line_count(), board.column_count()))
+            .collect()
     }
 
-    let score = non_winning_grid.calculate_score();
-    let last_called = last_called.expect("How do we win if we haven't called any number ?");
-    println!("Last winning grid with score of {}, last called number {}, final score: {}", score, last_called, last_called * score);
-}
\ No newline at end of file
+    macro_rules! grids_test {
+        ($name:ident, $content:literal => $sizes:expr) => {
+            #[test]
+            fn $name(){
+                assert_eq!(get_boards($content), $sizes);
+            }
+        };
+    }
+
+    // Sizes are (lines, columns)
+    grids_test!(grids_of_any_size,      "1 2 3\n4 5 6\n\n7 8\n9 10\n11 12" => vec![(2, 3), (3, 2)]);
+    grids_test!(several_blank_lines,    "\n\n1 2\n3 4\n\n\n\n5 6\n7 8\n\n\n" => vec![(2, 2), (2, 2)]);
+    grids_test!(blank_lines_of_spaces,  "1 2\n3 4\n  \n5 6\n7 8\n" => vec![(2, 2), (2, 2)]);
+    grids_test!(windows_line_endings,   "1 2\r\n3 4\r\n\r\n5\r\n6\r\n" => vec![(2, 2), (2, 1)]);
+    grids_test!(no_grid,                "\n\n" => Vec::<(usize, usize)>::new());
+
+    #[test]
+    fn numbers_are_kept_in_order(){
+        let boards = parse_boards(&read_lines(Cursor::new(" 1  2\r\n30 4 \r\n")));
+        assert_eq!((boards[0].get(0, 1), boards[0].get(1, 0)), (2, 30));
+    }
+
+    #[test]
+    #[should_panic(expected = "Grid 1 is not rectangular: expected 2 columns, got a line with 3")]
+    fn ragged_grid(){
+        get_boards("1 2\n3 4\n\n5 6\n7 8 9\n");
+    }
+}
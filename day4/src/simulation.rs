@@ -0,0 +1,164 @@
+use std::thread;
+
+use crate::board::Board;
+use crate::game::Game;
+use crate::rng::Rng;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct BoardTally {
+    first_wins: usize,
+    wins: usize,
+    winning_turns_sum: u64,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BoardOutcome {
+    // Boards winning together with the first winning number all count as first
+    pub first_win_probability: f64,
+    // None if the board never won in any trial
+    pub mean_winning_turn: Option<f64>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SimulationReport {
+    pub trials: usize,
+    pub boards: Vec<BoardOutcome>,
+}
+
+// Plays the boards against many shuffled copies of the draws. Each trial has its own random generator
+// derived from the seed, so the outcome is the same whatever the number of threads.
+pub fn simulate(boards: &[Board], draws: &[u32], trials: usize, seed: u64, thread_count: usize) -> SimulationReport {
+    let thread_count = thread_count.clamp(1, trials.max(1));
+    let trials_per_thread = trials.div_ceil(thread_count);
+
+    let tallies = thread::scope(|scope| {
+        let handles = (0..thread_count)
+            .map(|thread_idx| {
+                let first_trial = thread_idx * trials_per_thread;
+                let last_trial = (first_trial + trials_per_thread).min(trials);
+
+                scope.spawn(move || {
+                    let mut tallies = vec![BoardTally::default(); boards.len()];
+
+                    for trial in first_trial..last_trial {
+                        play_trial(boards, draws, trial_seed(seed, trial), &mut tallies);
+                    }
+
+                    tallies
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut tallies = vec![BoardTally::default(); boards.len()];
+        for handle in handles {
+            let thread_tallies = handle.join().expect("Simulation thread panicked");
+
+            for (tally, thread_tally) in tallies.iter_mut().zip(thread_tallies) {
+                tally.first_wins += thread_tally.first_wins;
+                tally.wins += thread_tally.wins;
+                tally.winning_turns_sum += thread_tally.winning_turns_sum;
+            }
+        }
+
+        tallies
+    });
+
+    let boards = tallies
+        .iter()
+        .map(|tally| BoardOutcome {
+            first_win_probability: tally.first_wins as f64 / trials.max(1) as f64,
+            mean_winning_turn: match tally.wins {
+                0 => None,
+                wins => Some(tally.winning_turns_sum as f64 / wins as f64),
+            },
+        })
+        .collect();
+
+    SimulationReport { trials, boards }
+}
+
+// The generator only adds a constant to its state at each step: seeds a multiple of that constant apart
+// give the same numbers shifted by a few steps. Going through the mixer once keeps the trials apart.
+fn trial_seed(seed: u64, trial: usize) -> u64 {
+    Rng::new(seed.wrapping_add(trial as u64)).next_u64()
+}
+
+fn play_trial(boards: &[Board], draws: &[u32], seed: u64, tallies: &mut [BoardTally]) {
+    let mut draws = draws.to_vec();
+    Rng::new(seed).shuffle(&mut draws);
+
+    let game = Game::play(boards, &draws);
+
+    if let Some((first_event, _)) = game.first_winner() {
+        for win in &first_event.winners {
+            tallies[win.board].first_wins += 1;
+        }
+    }
+
+    for (event, win) in game.win_order() {
+        tallies[win.board].wins += 1;
+        tallies[win.board].winning_turns_sum += event.turn as u64;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::{simulate, trial_seed};
+    use crate::board::Board;
+    use crate::rng::Rng;
+    use crate::win_rule::WinRule;
+
+    fn get_boards() -> Vec<Board> {
+        vec![
+            Board::new(&[1, 2, 3, 4], 2),
+            Board::new(&[5, 6, 7, 8], 2),
+            // Never complete, 10 is never drawn
+            Board::new(&[9, 10], 2).with_win_rules(&[WinRule::Blackout]),
+        ]
+    }
+
+    fn draws() -> Vec<u32> {
+        (1..=9).collect()
+    }
+
+    #[test]
+    fn trials_draw_different_numbers(){
+        let first_draws = |trial| {
+            let mut rng = Rng::new(trial_seed(0, trial));
+            (0..16).map(|_| rng.next_u64()).collect::<Vec<_>>()
+        };
+
+        // Neighbouring trials don't share any number, shifted or not
+        for trial in 0..8 {
+            let current = first_draws(trial).into_iter().collect::<HashSet<_>>();
+            assert!(first_draws(trial + 1).iter().all(|n| !current.contains(n)), "Trials {} and {}", trial, trial + 1);
+        }
+    }
+
+    #[test]
+    fn same_outcome_whatever_the_thread_count(){
+        let single_thread = simulate(&get_boards(), &draws(), 200, 42, 1);
+        let many_threads = simulate(&get_boards(), &draws(), 200, 42, 7);
+        assert_eq!(single_thread, many_threads);
+    }
+
+    #[test]
+    fn probabilities_make_sense(){
+        let report = simulate(&get_boards(), &draws(), 500, 1, 4);
+
+        // Both 2x2 boards are symmetrical
+        let first = report.boards[0].first_win_probability;
+        let second = report.boards[1].first_win_probability;
+        assert!((first + second - 1.0).abs() < 1e-9);
+        assert!((first - 0.5).abs() < 0.1);
+
+        // Two numbers out of nine at best, all the four in the worst case
+        let mean_turn = report.boards[0].mean_winning_turn.unwrap();
+        assert!((2.0..=9.0).contains(&mean_turn));
+
+        assert_eq!(report.boards[2].first_win_probability, 0.0);
+        assert_eq!(report.boards[2].mean_winning_turn, None);
+    }
+}
@@ -0,0 +1,235 @@
+use std::str::FromStr;
+
+use crate::board::Board;
+
+// What a board needs to have marked to win. A game can combine several of them, in which case
+// the board wins as soon as any of them is satisfied.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WinRule {
+    // The rule of the puzzle: one full line or one full column
+    LinesAndColumns,
+    // One of the two diagonals, only square grids have them
+    Diagonals,
+    FourCorners,
+    // Every number of the grid
+    Blackout,
+    // Every cell set in the mask, which only applies to grids of the same size
+    Mask(Mask),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mask {
+    line_count: usize,
+    column_count: usize,
+    // Same layout as the board numbers, one line after the other
+    cells: Vec<bool>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum WinRuleError {
+    UnknownRule(String),
+    EmptyMask,
+    NotRectangularMask,
+    UnknownMaskCell(char),
+}
+
+impl Mask {
+    pub fn new(cells: &[bool], column_count: usize) -> Self {
+        assert!(
+            column_count > 0 && !cells.is_empty() && cells.len().is_multiple_of(column_count),
+            "{} cells can't be arranged in lines of {} columns", cells.len(), column_count
+        );
+
+        Self {
+            line_count: cells.len() / column_count,
+            column_count,
+            cells: cells.to_vec(),
+        }
+    }
+
+    pub fn fits(&self, board: &Board) -> bool {
+        self.line_count == board.line_count() && self.column_count == board.column_count()
+    }
+
+    // Index of each cell that has to be marked, with the same layout as the board numbers
+    pub fn cells(&self) -> impl Iterator<Item = usize> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, set)| **set)
+            .map(|(idx, _)| idx)
+    }
+}
+
+impl WinRule {
+    // Every group of cells that makes the board win once they are all marked. The cells are indexes
+    // in a grid of that size where lines are placed one after the other.
+    pub fn patterns(&self, line_count: usize, column_count: usize) -> Vec<Vec<usize>> {
+        let idx = |line: usize, column: usize| line * column_count + column;
+        let last_line = line_count - 1;
+        let last_column = column_count - 1;
+
+        match self {
+            WinRule::LinesAndColumns => {
+                let lines = (0..line_count)
+                    .map(|line| (0..column_count).map(|column| idx(line, column)).collect());
+                let columns = (0..column_count)
+                    .map(|column| (0..line_count).map(|line| idx(line, column)).collect());
+
+                lines.chain(columns).collect()
+            },
+            WinRule::Diagonals => {
+                if line_count != column_count {
+                    return vec![];
+                }
+
+                vec![
+                    (0..line_count).map(|n| idx(n, n)).collect(),
+                    (0..line_count).map(|n| idx(n, last_column - n)).collect(),
+                ]
+            },
+            WinRule::FourCorners => {
+                let mut corners = vec![idx(0, 0), idx(0, last_column), idx(last_line, 0), idx(last_line, last_column)];
+                // Grids of one line or one column share some corners
+                corners.sort_unstable();
+                corners.dedup();
+                vec![corners]
+            },
+            WinRule::Blackout => vec![(0..line_count * column_count).collect()],
+            WinRule::Mask(mask) => {
+                if mask.line_count != line_count || mask.column_count != column_count {
+                    return vec![];
+                }
+
+                vec![mask.cells().collect()]
+            },
+        }
+    }
+}
+
+// Accepted forms: `lines`, `diagonals`, `corners`, `blackout` and `mask:<lines>`, where the lines of the
+// mask are separated by `/` and each cell is either `x` (has to be marked) or `.`, like `mask:x.x/.x./x.x`
+impl FromStr for WinRule {
+    type Err = WinRuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "lines" => WinRule::LinesAndColumns,
+            "diagonals" => WinRule::Diagonals,
+            "corners" => WinRule::FourCorners,
+            "blackout" => WinRule::Blackout,
+            _ => match s.strip_prefix("mask:") {
+                Some(mask) => WinRule::Mask(Mask::from_str(mask)?),
+                None => return Err(WinRuleError::UnknownRule(s.to_string())),
+            }
+        })
+    }
+}
+
+impl FromStr for Mask {
+    type Err = WinRuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines = s
+            .split('/')
+            .map(|line| line
+                .chars()
+                .map(|cell| match cell {
+                    'x' | 'X' | '1' => Ok(true),
+                    '.' | '0' => Ok(false),
+                    _ => Err(WinRuleError::UnknownMaskCell(cell)),
+                })
+                .collect::<Result<Vec<bool>, _>>())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let column_count = lines[0].len();
+        if column_count == 0 {
+            return Err(WinRuleError::EmptyMask);
+        }
+
+        if lines.iter().any(|line| line.len() != column_count) {
+            return Err(WinRuleError::NotRectangularMask);
+        }
+
+        let cells = lines.concat();
+        if !cells.contains(&true) {
+            return Err(WinRuleError::EmptyMask);
+        }
+
+        Ok(Mask::new(&cells, column_count))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::{WinRule, WinRuleError};
+    use crate::board::Board;
+
+    // 1  2  3
+    // 4  5  6
+    // 7  8  9
+    fn get_board(marked: &[u32]) -> Board {
+        let mut board = Board::new(&[1, 2, 3, 4, 5, 6, 7, 8, 9], 3);
+        for number in marked {
+            board.mark(*number);
+        }
+        board
+    }
+
+    macro_rules! rule_test {
+        ($name:ident, $rule:literal, [$($marked:literal),*] -> $outcome:literal) => {
+            #[test]
+            fn $name(){
+                let rule = WinRule::from_str($rule).expect("Rule should parse");
+                let board = get_board(&[$($marked),*]).with_win_rules(&[rule]);
+                assert_eq!(board.is_win(), $outcome);
+            }
+        };
+    }
+
+    rule_test!(line_wins,                   "lines", [4, 5, 6] -> true);
+    rule_test!(column_wins,                 "lines", [2, 5, 8] -> true);
+    rule_test!(diagonal_is_not_a_line,      "lines", [1, 5, 9] -> false);
+
+    rule_test!(main_diagonal_wins,          "diagonals", [1, 5, 9] -> true);
+    rule_test!(anti_diagonal_wins,          "diagonals", [3, 5, 7] -> true);
+    rule_test!(partial_diagonal,            "diagonals", [1, 5, 7] -> false);
+
+    rule_test!(four_corners_win,            "corners", [1, 3, 7, 9] -> true);
+    rule_test!(three_corners,               "corners", [1, 3, 7, 5] -> false);
+
+    rule_test!(blackout_wins,               "blackout", [1, 2, 3, 4, 5, 6, 7, 8, 9] -> true);
+    rule_test!(blackout_missing_one,        "blackout", [1, 2, 3, 4, 6, 7, 8, 9] -> false);
+
+    rule_test!(cross_mask_wins,             "mask:.x./xxx/.x.", [2, 4, 5, 6, 8] -> true);
+    rule_test!(cross_mask_incomplete,       "mask:.x./xxx/.x.", [2, 4, 5, 6] -> false);
+    rule_test!(mask_of_other_size,          "mask:xx/xx", [1, 2, 4, 5] -> false);
+
+    #[test]
+    fn no_diagonal_on_rectangular_grid(){
+        let mut board = Board::new(&[1, 2, 3, 4, 5, 6], 3).with_win_rules(&[WinRule::Diagonals]);
+        board.mark(1);
+        board.mark(5);
+        assert!(!board.is_win());
+    }
+
+    #[test]
+    fn rules_can_be_combined(){
+        let rules = [WinRule::Diagonals, WinRule::FourCorners];
+        let mut board = Board::new(&[1, 2, 3, 4, 5, 6, 7, 8, 9], 3).with_win_rules(&rules);
+        for number in [1, 3, 7] {
+            assert!(!board.mark(number));
+        }
+        assert!(board.mark(9));
+    }
+
+    #[test]
+    fn invalid_rules(){
+        assert_eq!(WinRule::from_str("stars"), Err(WinRuleError::UnknownRule("stars".to_string())));
+        assert_eq!(WinRule::from_str("mask:x./x"), Err(WinRuleError::NotRectangularMask));
+        assert_eq!(WinRule::from_str("mask:../.."), Err(WinRuleError::EmptyMask));
+        assert_eq!(WinRule::from_str("mask:x?"), Err(WinRuleError::UnknownMaskCell('?')));
+    }
+}
@@ -0,0 +1,82 @@
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::population::{Lifecycle, Population};
+
+pub struct Measure {
+    pub name: &'static str,
+    pub count: u64,
+    // Time taken by all the repetitions
+    pub elapsed: Duration,
+}
+
+// The simulation as first written: the group of timer zero is removed from the front of the vector,
+// shifting every other group down by one
+fn count_by_shifting(lifecycle: &Lifecycle, timers: &[usize], days: usize) -> u64 {
+    let mut groups = vec![0u64; lifecycle.state_count()];
+    for timer in timers {
+        groups[*timer] += 1;
+    }
+
+    for _ in 0..days {
+        let stage_zero = groups.remove(0);
+        groups.push(0);
+
+        groups[lifecycle.newborn_timer] += stage_zero;
+        groups[lifecycle.reset_timer] += stage_zero;
+    }
+
+    groups.iter().sum()
+}
+
+fn count_with_ring(lifecycle: &Lifecycle, timers: &[usize], days: usize) -> u64 {
+    Population::new(*lifecycle, timers).simulate(days, false).expect("The count was checked before the benchmark").0
+}
+
+fn measure(name: &'static str, repetitions: u32, f: impl Fn() -> u64) -> Measure {
+    let count = f();
+
+    let start = Instant::now();
+    for _ in 0..repetitions {
+        black_box(f());
+    }
+
+    Measure { name, count, elapsed: start.elapsed() }
+}
+
+// Times both ways of simulating the days. Only meaningful in release mode.
+pub fn run(lifecycle: &Lifecycle, timers: &[usize], days: usize, repetitions: u32) -> Vec<Measure> {
+    let timers = black_box(timers);
+
+    let measures = vec![
+        measure("shifting vector", repetitions, || count_by_shifting(lifecycle, timers, days)),
+        measure("ring buffer", repetitions, || count_with_ring(lifecycle, timers, days)),
+    ];
+
+    assert!(
+        measures.iter().all(|m| m.count == measures[0].count),
+        "Both simulations should count the same fishes"
+    );
+
+    measures
+}
+
+#[cfg(test)]
+mod test {
+    use super::{count_by_shifting, count_with_ring};
+    use crate::population::Lifecycle;
+
+    #[test]
+    fn both_simulations_agree(){
+        let lifecycles = [Lifecycle::default(), Lifecycle { newborn_timer: 2, reset_timer: 4 }];
+
+        for lifecycle in &lifecycles {
+            for days in [0, 1, 17, 80] {
+                assert_eq!(
+                    count_by_shifting(lifecycle, &[3, 4, 3, 1, 2], days),
+                    count_with_ring(lifecycle, &[3, 4, 3, 1, 2], days)
+                );
+            }
+        }
+    }
+}
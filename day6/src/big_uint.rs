@@ -0,0 +1,152 @@
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, Mul};
+
+// Unsigned integer as big as needed. Only what the lanternfish counting needs: additions,
+// multiplications and printing in base 10.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigUint {
+    // Least significant digit first, in base 2^32, without trailing zeros (zero has no digit)
+    digits: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        Self { digits: Vec::new() }
+    }
+
+    fn normalize(mut self) -> Self {
+        while self.digits.last() == Some(&0) {
+            self.digits.pop();
+        }
+        self
+    }
+
+    // Divides in place and returns the remainder
+    fn div_rem_small(&mut self, divisor: u32) -> u32 {
+        let mut remainder = 0u64;
+
+        for digit in self.digits.iter_mut().rev() {
+            let current = (remainder << 32) | *digit as u64;
+            *digit = (current / divisor as u64) as u32;
+            remainder = current % divisor as u64;
+        }
+
+        while self.digits.last() == Some(&0) {
+            self.digits.pop();
+        }
+
+        remainder as u32
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(n: u64) -> Self {
+        Self { digits: vec![n as u32, (n >> 32) as u32] }.normalize()
+    }
+}
+
+impl Add for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        let (long, short) = if self.digits.len() >= other.digits.len() { (self, other) } else { (other, self) };
+        let mut digits = Vec::with_capacity(long.digits.len() + 1);
+        let mut carry = 0u64;
+
+        for (idx, digit) in long.digits.iter().enumerate() {
+            let sum = *digit as u64 + *short.digits.get(idx).unwrap_or(&0) as u64 + carry;
+            digits.push(sum as u32);
+            carry = sum >> 32;
+        }
+
+        if carry > 0 {
+            digits.push(carry as u32);
+        }
+
+        BigUint { digits }
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: &BigUint) -> BigUint {
+        if self.digits.is_empty() || other.digits.is_empty() {
+            return BigUint::zero();
+        }
+
+        let mut digits = vec![0u32; self.digits.len() + other.digits.len()];
+
+        for (i, a) in self.digits.iter().enumerate() {
+            let mut carry = 0u64;
+
+            for (j, b) in other.digits.iter().enumerate() {
+                // Can't overflow: (2^32 - 1)^2 + 2 * (2^32 - 1) = 2^64 - 1
+                let current = *a as u64 * *b as u64 + digits[i + j] as u64 + carry;
+                digits[i + j] = current as u32;
+                carry = current >> 32;
+            }
+
+            digits[i + other.digits.len()] = carry as u32;
+        }
+
+        BigUint { digits }.normalize()
+    }
+}
+
+impl Display for BigUint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        const CHUNK: u32 = 1_000_000_000;
+
+        if self.digits.is_empty() {
+            return write!(f, "0");
+        }
+
+        // Groups of 9 decimal digits, least significant first
+        let mut rest = self.clone();
+        let mut chunks = Vec::new();
+        while !rest.digits.is_empty() {
+            chunks.push(rest.div_rem_small(CHUNK));
+        }
+
+        let mut chunks = chunks.iter().rev();
+        write!(f, "{}", chunks.next().unwrap())?;
+        for chunk in chunks {
+            write!(f, "{:09}", chunk)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BigUint;
+
+    #[test]
+    fn small_numbers(){
+        assert_eq!(BigUint::zero().to_string(), "0");
+        assert_eq!(BigUint::from(0).to_string(), "0");
+        assert_eq!(BigUint::from(u64::MAX).to_string(), "18446744073709551615");
+        assert_eq!((&BigUint::from(40) + &BigUint::from(2)).to_string(), "42");
+        assert_eq!((&BigUint::from(6) * &BigUint::from(7)).to_string(), "42");
+    }
+
+    #[test]
+    fn carries(){
+        let max = BigUint::from(u64::MAX);
+        assert_eq!((&max + &BigUint::from(1)).to_string(), "18446744073709551616");
+        assert_eq!((&max * &max).to_string(), "340282366920938463426481119284349108225");
+        assert_eq!((&max * &BigUint::zero()), BigUint::zero());
+    }
+
+    #[test]
+    fn power_of_ten(){
+        let ten = BigUint::from(10);
+        let mut power = BigUint::from(1);
+        for _ in 0..40 {
+            power = &power * &ten;
+        }
+        assert_eq!(power.to_string(), format!("1{}", "0".repeat(40)));
+    }
+}
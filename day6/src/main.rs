@@ -1,35 +1,99 @@
+use std::convert::TryFrom;
 use std::env::args;
 use std::str::FromStr;
 
-const ITERATION_COUNT: u32 = 256;
-const GROWTH_STATE_COUNT: usize = 9; // 0 to 8 incl.
-const AFTER_NEW_FISH_PLACE: usize = 6; // Stage number
+use matrix::{Exact, Modulo};
+use population::{parse_timers, Lifecycle, Population};
+
+mod benchmark;
+mod big_uint;
+mod matrix;
+mod population;
+
+const DEFAULT_DAY_COUNT: u64 = 256;
+const DEFAULT_REPETITIONS: u32 = 100_000;
+
+const USAGE: &str = "USAGE: day6 <input file> [--days <count>] [--newborn-timer <timer>] [--reset-timer <timer>] [--trace|--fast [--modulus <modulus>]|--benchmark [--repetitions <count>]]";
 
 fn main() {
-    let filename = args().nth(1).expect("USAGE: day6 <input file>");
-    let content = std::fs::read_to_string(filename).unwrap();
+    let arguments = args().skip(1).collect::<Vec<_>>();
 
-    let lanternfish_array = content.split(',')
-        .map(|fish| usize::from_str(fish))
-        .map(Result::unwrap)
-        .collect::<Vec<usize>>();
+    let mut filename = None;
+    let mut day_count = DEFAULT_DAY_COUNT;
+    let mut lifecycle = Lifecycle::default();
+    let mut trace = false;
+    let mut fast = false;
+    let mut modulus = None;
+    let mut benchmark = false;
+    let mut repetitions = DEFAULT_REPETITIONS;
 
-    let mut lanternfish_groups: Vec<u64> = vec![0; GROWTH_STATE_COUNT];
+    let mut arguments_iter = arguments.iter();
+    while let Some(argument) = arguments_iter.next() {
+        let mut next_number = || arguments_iter.next().and_then(|n| u64::from_str(n).ok()).expect(USAGE);
 
-    for lanternfish_timer in &lanternfish_array {
-        lanternfish_groups[*lanternfish_timer] += 1;
+        if argument == "--days" {
+            day_count = next_number();
+        } else if argument == "--newborn-timer" {
+            lifecycle.newborn_timer = next_number() as usize;
+        } else if argument == "--reset-timer" {
+            lifecycle.reset_timer = next_number() as usize;
+        } else if argument == "--trace" {
+            trace = true;
+        } else if argument == "--fast" {
+            fast = true;
+        } else if argument == "--modulus" {
+            // Only the fast mode can count modulo something
+            fast = true;
+            modulus = Some(next_number()).filter(|m| *m > 0);
+            assert!(modulus.is_some(), "The modulus can't be zero");
+        } else if argument == "--benchmark" {
+            benchmark = true;
+        } else if argument == "--repetitions" {
+            repetitions = next_number() as u32;
+        } else {
+            filename = Some(argument);
+        }
     }
 
-    // Now the simulation of the fishes
-    for iteration in 1..=ITERATION_COUNT {
-        // Add the fishes from stage zero into the latest stage possible
-        let stage_zero = lanternfish_groups.remove(0);
-        lanternfish_groups.push(stage_zero);
+    let content = std::fs::read_to_string(filename.expect(USAGE)).unwrap();
 
-        // The fishes we have taken from stage zero by poping the vector still exist !
-        lanternfish_groups[AFTER_NEW_FISH_PLACE] += stage_zero;
+    let lanternfish_array = parse_timers(&content, &lifecycle).unwrap_or_else(|e| panic!("Invalid input: {:?}", e));
 
-        let fishes_count = lanternfish_groups.iter().sum::<u64>();
-        println!("Iteration {}: groups: {:?}; count: {}", iteration, lanternfish_groups, fishes_count);
+    let mut population = Population::new(lifecycle, &lanternfish_array);
+
+    // Exact counts take a few seconds for a million days in release mode, the numbers get too big to be
+    // computed quickly after that
+    if fast {
+        match modulus {
+            Some(modulus) => println!(
+                "Lanternfishes after {} days: {} (modulo {})",
+                day_count, population.count_after(day_count, &Modulo(modulus)), modulus
+            ),
+            None => println!("Lanternfishes after {} days: {}", day_count, population.count_after(day_count, &Exact)),
+        }
+        return;
     }
+
+    let day_count = usize::try_from(day_count).expect("Too many days to go through each of them, use --fast");
+
+    // Also makes sure the count fits before timing the benchmark
+    let (fishes_count, history) = population
+        .simulate(day_count, trace)
+        .unwrap_or_else(|e| panic!("{:?}: too many fishes to count in 64 bits, use --fast", e));
+
+    if benchmark {
+        for measure in benchmark::run(&lifecycle, &lanternfish_array, day_count, repetitions) {
+            println!(
+                "{}: {} fishes, {:?} per simulation ({:?} in total)",
+                measure.name, measure.count, measure.elapsed / repetitions.max(1), measure.elapsed
+            );
+        }
+        return;
+    }
+
+    for snapshot in history.iter().flatten() {
+        println!("Day {}: groups: {:?}; count: {}", snapshot.day, snapshot.groups, snapshot.count);
+    }
+
+    println!("Lanternfishes after {} days: {}", day_count, fishes_count);
 }
@@ -0,0 +1,154 @@
+use crate::big_uint::BigUint;
+use crate::population::Lifecycle;
+
+// How the numbers of the matrix are computed: exactly, or modulo some number when exact numbers would
+// be way too big
+pub trait Arithmetic {
+    type Value: Clone;
+
+    fn value_of(&self, n: u64) -> Self::Value;
+    fn add(&self, a: &Self::Value, b: &Self::Value) -> Self::Value;
+    fn mul(&self, a: &Self::Value, b: &Self::Value) -> Self::Value;
+}
+
+pub struct Exact;
+
+pub struct Modulo(pub u64);
+
+impl Arithmetic for Exact {
+    type Value = BigUint;
+
+    fn value_of(&self, n: u64) -> BigUint {
+        BigUint::from(n)
+    }
+
+    fn add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a + b
+    }
+
+    fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a * b
+    }
+}
+
+impl Arithmetic for Modulo {
+    type Value = u64;
+
+    fn value_of(&self, n: u64) -> u64 {
+        n % self.0
+    }
+
+    fn add(&self, a: &u64, b: &u64) -> u64 {
+        ((*a as u128 + *b as u128) % self.0 as u128) as u64
+    }
+
+    fn mul(&self, a: &u64, b: &u64) -> u64 {
+        ((*a as u128 * *b as u128) % self.0 as u128) as u64
+    }
+}
+
+// Square matrix, one line after the other
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix<T> {
+    size: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Matrix<T> {
+    fn from_fn<A: Arithmetic<Value = T>>(size: usize, arithmetic: &A, f: impl Fn(usize, usize) -> u64) -> Self {
+        let cells = (0..size * size)
+            .map(|idx| arithmetic.value_of(f(idx / size, idx % size)))
+            .collect();
+
+        Self { size, cells }
+    }
+
+    fn identity<A: Arithmetic<Value = T>>(size: usize, arithmetic: &A) -> Self {
+        Self::from_fn(size, arithmetic, |line, column| (line == column) as u64)
+    }
+
+    fn get(&self, line: usize, column: usize) -> &T {
+        &self.cells[line * self.size + column]
+    }
+
+    // One day of the lanternfish life: the groups of the next day are this matrix times the groups
+    // of the current day
+    pub fn transition<A: Arithmetic<Value = T>>(lifecycle: &Lifecycle, arithmetic: &A) -> Self {
+        Self::from_fn(lifecycle.state_count(), arithmetic, |line, column| {
+            // Every timer goes down by one, the fishes at zero come back at the reset timer and give birth
+            let counts_down = (column == line + 1) as u64;
+            let from_zero = (column == 0 && line == lifecycle.reset_timer) as u64
+                + (column == 0 && line == lifecycle.newborn_timer) as u64;
+
+            counts_down + from_zero
+        })
+    }
+
+    pub fn multiply<A: Arithmetic<Value = T>>(&self, other: &Self, arithmetic: &A) -> Self {
+        let cells = (0..self.size * self.size)
+            .map(|idx| {
+                let (line, column) = (idx / self.size, idx % self.size);
+
+                (1..self.size).fold(arithmetic.mul(self.get(line, 0), other.get(0, column)), |sum, k| {
+                    arithmetic.add(&sum, &arithmetic.mul(self.get(line, k), other.get(k, column)))
+                })
+            })
+            .collect();
+
+        Self { size: self.size, cells }
+    }
+
+    // Exponentiation by squaring, only takes about 2 * log2(exponent) multiplications
+    pub fn pow<A: Arithmetic<Value = T>>(&self, mut exponent: u64, arithmetic: &A) -> Self {
+        let mut result = Self::identity(self.size, arithmetic);
+        let mut square = self.clone();
+
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.multiply(&square, arithmetic);
+            }
+
+            exponent >>= 1;
+            if exponent > 0 {
+                square = square.multiply(&square, arithmetic);
+            }
+        }
+
+        result
+    }
+
+    pub fn apply<A: Arithmetic<Value = T>>(&self, vector: &[T], arithmetic: &A) -> Vec<T> {
+        (0..self.size)
+            .map(|line| {
+                (1..self.size).fold(arithmetic.mul(self.get(line, 0), &vector[0]), |sum, k| {
+                    arithmetic.add(&sum, &arithmetic.mul(self.get(line, k), &vector[k]))
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Matrix, Modulo};
+    use crate::population::Lifecycle;
+
+    #[test]
+    fn transition_of_the_puzzle(){
+        let matrix = Matrix::transition(&Lifecycle::default(), &Modulo(u64::MAX));
+        let groups = [1, 1, 2, 1, 0, 0, 0, 0, 0];
+        assert_eq!(matrix.apply(&groups, &Modulo(u64::MAX)), vec![1, 2, 1, 0, 0, 0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn power_is_repeated_multiplication(){
+        let arithmetic = Modulo(1_000_000_007);
+        let matrix = Matrix::transition(&Lifecycle::default(), &arithmetic);
+
+        let mut repeated = Matrix::identity(9, &arithmetic);
+        for exponent in 0..40 {
+            assert_eq!(matrix.pow(exponent, &arithmetic), repeated);
+            repeated = repeated.multiply(&matrix, &arithmetic);
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use std::str::FromStr;
+
+use crate::matrix::{Arithmetic, Matrix};
+
+// Timers of the lanternfish life: a new fish starts at `newborn_timer` and a fish that just gave birth
+// starts again at `reset_timer`. Both count down by one each day and a fish gives birth once its timer
+// would go below zero.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lifecycle {
+    pub newborn_timer: usize,
+    pub reset_timer: usize,
+}
+
+impl Default for Lifecycle {
+    // The lifecycle of the puzzle
+    fn default() -> Self {
+        Self {
+            newborn_timer: 8,
+            reset_timer: 6,
+        }
+    }
+}
+
+impl Lifecycle {
+    // Number of different timer values a fish can have, 0 included
+    pub fn state_count(&self) -> usize {
+        self.newborn_timer.max(self.reset_timer) + 1
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    // Positions start at 1 for the first fish of the list
+    InvalidTimer { position: usize, text: String },
+    TimerOutOfLifecycle { position: usize, timer: usize, max_timer: usize },
+}
+
+// Timers separated by commas, like `3,4,3,1,2`. Whitespace around the timers and at the end of the
+// file doesn't matter.
+pub fn parse_timers(content: &str, lifecycle: &Lifecycle) -> Result<Vec<usize>, ParseError> {
+    let content = content.trim();
+    if content.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let max_timer = lifecycle.state_count() - 1;
+
+    content
+        .split(',')
+        .map(str::trim)
+        .enumerate()
+        .map(|(idx, text)| {
+            let position = idx + 1;
+            let timer = usize::from_str(text)
+                .map_err(|_| ParseError::InvalidTimer { position, text: text.to_string() })?;
+
+            if timer > max_timer {
+                return Err(ParseError::TimerOutOfLifecycle { position, timer, max_timer });
+            }
+
+            Ok(timer)
+        })
+        .collect()
+}
+
+// The count of fishes doesn't fit in 64 bits anymore at the end of that day
+#[derive(Debug, PartialEq)]
+pub struct Overflow {
+    pub day: usize,
+}
+
+// State of the population at the end of one day
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub day: usize,
+    // Number of fishes for each timer value
+    pub groups: Vec<u64>,
+    pub count: u64,
+}
+
+// Fishes with the same timer all behave the same, so we only keep how many fishes have each timer.
+// The groups are a ring: instead of moving every group down one timer each day, the index of the group
+// at timer zero moves up by one.
+#[derive(Debug, Clone)]
+pub struct Population {
+    lifecycle: Lifecycle,
+    groups: Vec<u64>,
+    // Index of the fishes with a timer of zero
+    start: usize,
+    day: usize,
+}
+
+impl Population {
+    pub fn new(lifecycle: Lifecycle, timers: &[usize]) -> Self {
+        let mut groups = vec![0; lifecycle.state_count()];
+
+        for timer in timers {
+            assert!(*timer < groups.len(), "A fish can't have a timer of {} in that lifecycle", timer);
+            groups[*timer] += 1;
+        }
+
+        Self { lifecycle, groups, start: 0, day: 0 }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.groups.iter().sum()
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            day: self.day,
+            groups: self.groups_by_timer(),
+            count: self.count(),
+        }
+    }
+
+    // Number of fishes for each timer value, starting at zero
+    pub fn groups_by_timer(&self) -> Vec<u64> {
+        let (before_start, from_start) = self.groups.split_at(self.start);
+        [from_start, before_start].concat()
+    }
+
+    fn group_mut(&mut self, timer: usize) -> &mut u64 {
+        let len = self.groups.len();
+        &mut self.groups[(self.start + timer) % len]
+    }
+
+    // Each fish of stage zero adds one newborn to the count. As long as the count fits, so does every
+    // group, which is why only the count is checked.
+    pub fn advance(&mut self) -> Result<(), Overflow> {
+        if self.count().checked_add(self.groups[self.start]).is_none() {
+            return Err(Overflow { day: self.day + 1 });
+        }
+
+        // Once the ring has turned, the slot of timer zero is the one of the highest timer
+        let stage_zero = std::mem::take(&mut self.groups[self.start]);
+        self.start = (self.start + 1) % self.groups.len();
+
+        // The fishes from stage zero give birth and start again
+        *self.group_mut(self.lifecycle.newborn_timer) += stage_zero;
+        *self.group_mut(self.lifecycle.reset_timer) += stage_zero;
+
+        self.day += 1;
+
+        Ok(())
+    }
+
+    // Count of fishes once `days` more days have passed, along with the state at the end of each of
+    // these days if asked for. The population stops at the last day with a count that fits in 64 bits.
+    pub fn simulate(&mut self, days: usize, keep_history: bool) -> Result<(u64, Option<Vec<Snapshot>>), Overflow> {
+        let mut history = if keep_history { Some(Vec::with_capacity(days)) } else { None };
+
+        for _ in 0..days {
+            self.advance()?;
+
+            if let Some(history) = &mut history {
+                history.push(self.snapshot());
+            }
+        }
+
+        Ok((self.count(), history))
+    }
+
+    // Count of fishes once `days` more days have passed, without going through each day. The population
+    // itself doesn't change.
+    pub fn count_after<A: Arithmetic>(&self, days: u64, arithmetic: &A) -> A::Value {
+        let groups = self.groups_by_timer()
+            .iter()
+            .map(|count| arithmetic.value_of(*count))
+            .collect::<Vec<_>>();
+
+        let groups = Matrix::transition(&self.lifecycle, arithmetic)
+            .pow(days, arithmetic)
+            .apply(&groups, arithmetic);
+
+        groups
+            .iter()
+            .skip(1)
+            .fold(groups[0].clone(), |sum, count| arithmetic.add(&sum, count))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_timers, Lifecycle, Overflow, ParseError, Population};
+    use crate::matrix::{Exact, Modulo};
+
+    const EXAMPLE_TIMERS: [usize; 5] = [3, 4, 3, 1, 2];
+
+    macro_rules! count_test {
+        ($name:ident, $days:literal -> $count:literal) => {
+            #[test]
+            fn $name(){
+                let mut population = Population::new(Lifecycle::default(), &EXAMPLE_TIMERS);
+                assert_eq!(population.simulate($days, false), Ok(($count, None)));
+            }
+        };
+    }
+
+    count_test!(example_18_days,    18 -> 26);
+    count_test!(example_80_days,    80 -> 5934);
+    count_test!(example_256_days,   256 -> 26984457539);
+
+    macro_rules! count_after_test {
+        ($name:ident, $days:literal -> $count:literal, $arithmetic:expr) => {
+            #[test]
+            fn $name(){
+                let population = Population::new(Lifecycle::default(), &EXAMPLE_TIMERS);
+                assert_eq!(population.count_after($days, &$arithmetic).to_string(), $count);
+            }
+        };
+    }
+
+    count_after_test!(fast_example_80_days,     80 -> "5934", Exact);
+    count_after_test!(fast_example_256_days,    256 -> "26984457539", Exact);
+    count_after_test!(fast_example_0_days,      0 -> "5", Exact);
+    count_after_test!(fast_beyond_u64,          1000 -> "379589061144698259131825683795505058481", Exact);
+    count_after_test!(fast_with_modulus,        256 -> "984457357", Modulo(1_000_000_007));
+
+    #[test]
+    fn fast_matches_one_day_at_a_time(){
+        let lifecycle = Lifecycle { newborn_timer: 5, reset_timer: 3 };
+        let mut population = Population::new(lifecycle, &EXAMPLE_TIMERS);
+        let fast = population.count_after(150, &Exact);
+        assert_eq!(fast.to_string(), population.simulate(150, false).unwrap().0.to_string());
+    }
+
+    macro_rules! parse_test {
+        ($name:ident, $content:literal -> $result:expr) => {
+            #[test]
+            fn $name(){
+                assert_eq!(parse_timers($content, &Lifecycle::default()), $result);
+            }
+        };
+    }
+
+    parse_test!(parse_example,              "3,4,3,1,2" -> Ok(vec![3, 4, 3, 1, 2]));
+    parse_test!(parse_trailing_newline,     "3,4,3,1,2\n" -> Ok(vec![3, 4, 3, 1, 2]));
+    parse_test!(parse_crlf_and_spaces,      " 3, 4 ,3\r\n" -> Ok(vec![3, 4, 3]));
+    parse_test!(parse_no_fish,              "\n" -> Ok(vec![]));
+    parse_test!(parse_not_a_number,         "3,4,x,1" -> Err(ParseError::InvalidTimer { position: 3, text: "x".to_string() }));
+    parse_test!(parse_missing_timer,        "3,,1" -> Err(ParseError::InvalidTimer { position: 2, text: "".to_string() }));
+    parse_test!(parse_negative_timer,       "-1" -> Err(ParseError::InvalidTimer { position: 1, text: "-1".to_string() }));
+    parse_test!(parse_timer_too_high,       "3,4,9" -> Err(ParseError::TimerOutOfLifecycle { position: 3, timer: 9, max_timer: 8 }));
+
+    #[test]
+    fn parse_with_longer_lifecycle(){
+        let lifecycle = Lifecycle { newborn_timer: 10, reset_timer: 6 };
+        assert_eq!(parse_timers("9,10", &lifecycle), Ok(vec![9, 10]));
+    }
+
+    #[test]
+    fn history_of_each_day(){
+        let mut population = Population::new(Lifecycle::default(), &EXAMPLE_TIMERS);
+        let (count, history) = population.simulate(3, true).unwrap();
+        let history = history.unwrap();
+
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].day, 1);
+        assert_eq!(history[0].groups, vec![1, 1, 2, 1, 0, 0, 0, 0, 0]);
+        assert_eq!(history[1].count, 6);
+        assert_eq!(history[2].count, count);
+    }
+
+    #[test]
+    fn custom_lifecycle(){
+        // Every fish gives birth every day to a fish that does the same
+        let lifecycle = Lifecycle { newborn_timer: 0, reset_timer: 0 };
+        let mut population = Population::new(lifecycle, &[0, 0, 0]);
+        assert_eq!(population.simulate(10, false).unwrap().0, 3 * 1024);
+    }
+
+    #[test]
+    fn reset_timer_after_newborn_timer(){
+        // 2 at day 0 → 1 → 0 → gives birth to a fish at 0 and goes back to 3
+        let lifecycle = Lifecycle { newborn_timer: 0, reset_timer: 3 };
+        let mut population = Population::new(lifecycle, &[2]);
+        assert_eq!(population.simulate(3, false).unwrap().0, 2);
+        assert_eq!(population.snapshot().groups, vec![1, 0, 0, 1]);
+    }
+
+    #[test]
+    fn fast_count_after_the_ring_has_turned(){
+        let mut population = Population::new(Lifecycle::default(), &EXAMPLE_TIMERS);
+        population.simulate(13, false).unwrap();
+        assert_eq!(population.count_after(67, &Exact).to_string(), "5934");
+    }
+
+    #[test]
+    fn overflow(){
+        // Doubling every day, 3 fishes still fit after 62 days but not after 63
+        let lifecycle = Lifecycle { newborn_timer: 0, reset_timer: 0 };
+        let mut population = Population::new(lifecycle, &[0, 0, 0]);
+
+        assert_eq!(population.simulate(62, false), Ok((3 << 62, None)));
+        assert_eq!(population.simulate(5, false), Err(Overflow { day: 63 }));
+        // The population stays on the last day that fits
+        assert_eq!(population.snapshot().count, 3 << 62);
+    }
+
+    #[test]
+    fn example_overflows_before_600_days(){
+        let mut population = Population::new(Lifecycle::default(), &EXAMPLE_TIMERS);
+        assert!(population.simulate(600, false).is_err());
+    }
+}
@@ -0,0 +1,163 @@
+use crate::Alignment;
+
+// One line per position, with a header
+pub fn to_csv(curve: &[Alignment]) -> String {
+    let mut csv = String::from("position,fuel\n");
+
+    for point in curve {
+        csv += &format!("{},{}\n", point.position, point.fuel);
+    }
+
+    csv
+}
+
+// Plots the fuel needed for each position, the cheapest one being marked with `o` and a `^` under the
+// axis. When there are more positions than columns, each column shows the cheapest of its positions.
+pub fn to_plot(curve: &[Alignment], optimum: &Alignment, width: usize, height: usize) -> String {
+    assert!(!curve.is_empty() && width > 0 && height > 1, "Nothing to plot");
+
+    // Column `c` gets the positions from `c * len / width` up to the next column, so the position at
+    // `idx` is in the last column starting at or before it
+    let width = width.min(curve.len());
+    let columns = (0..width)
+        .map(|column| {
+            let positions = &curve[column * curve.len() / width..(column + 1) * curve.len() / width];
+            positions.iter().map(|point| point.fuel).min().unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let optimum_column = curve
+        .iter()
+        .position(|point| point.position == optimum.position)
+        .map(|idx| ((idx + 1) * width - 1) / curve.len())
+        .expect("The optimum should be on the curve");
+
+    let max_fuel = *columns.iter().max().unwrap();
+    let min_fuel = *columns.iter().min().unwrap();
+    // Row 0 is the top one
+    let row_of = |fuel: u128| match max_fuel - min_fuel {
+        0 => height - 1,
+        range => ((max_fuel - fuel) as f64 / range as f64 * (height - 1) as f64).round() as usize,
+    };
+
+    let label_width = max_fuel.to_string().len();
+    let mut plot = String::new();
+
+    for row in 0..height {
+        let label = match row {
+            0 => max_fuel.to_string(),
+            _ if row == height - 1 => min_fuel.to_string(),
+            _ => String::new(),
+        };
+
+        let line = columns
+            .iter()
+            .enumerate()
+            .map(|(column, fuel)| match (row_of(*fuel) == row, column == optimum_column) {
+                (true, true) => 'o',
+                (true, false) => '*',
+                _ => ' ',
+            })
+            .collect::<String>();
+
+        plot += &format!("{:>width$} |{}\n", label, line.trim_end(), width = label_width);
+    }
+
+    plot += &format!("{:>width$} +{}\n", "", "-".repeat(width), width = label_width);
+    plot += &format!("{:>width$}  {}^\n", "", " ".repeat(optimum_column), width = label_width);
+    plot += &format!(
+        "Positions {} to {}, cheapest: {} at position {}\n",
+        curve[0].position, curve[curve.len() - 1].position, optimum.fuel, optimum.position
+    );
+
+    plot
+}
+
+#[cfg(test)]
+mod test {
+    use super::{to_csv, to_plot};
+    use crate::Alignment;
+
+    fn curve(fuels: &[u128]) -> Vec<Alignment> {
+        fuels
+            .iter()
+            .enumerate()
+            .map(|(position, fuel)| Alignment { position: position as u32, fuel: *fuel })
+            .collect()
+    }
+
+    #[test]
+    fn csv(){
+        assert_eq!(to_csv(&curve(&[3, 1, 4])), "position,fuel\n0,3\n1,1\n2,4\n");
+    }
+
+    #[test]
+    fn plot_one_column_per_position(){
+        let curve = curve(&[10, 5, 0, 5, 10]);
+        let plot = to_plot(&curve, &curve[2], 80, 3);
+
+        assert_eq!(plot, concat!(
+            "10 |*   *\n",
+            "   | * *\n",
+            " 0 |  o\n",
+            "   +-----\n",
+            "      ^\n",
+            "Positions 0 to 4, cheapest: 0 at position 2\n",
+        ));
+    }
+
+    #[test]
+    fn plot_several_positions_per_column(){
+        let curve = curve(&[8, 6, 4, 2, 1, 3, 5, 7]);
+        let plot = to_plot(&curve, &curve[4], 4, 2);
+
+        // Columns get positions 0-1, 2-3, 4-5 and 6-7
+        assert_eq!(plot, concat!(
+            "6 |*  *\n",
+            "1 | *o\n",
+            "  +----\n",
+            "     ^\n",
+            "Positions 0 to 7, cheapest: 1 at position 4\n",
+        ));
+    }
+
+    #[test]
+    fn plot_uneven_columns(){
+        let curve = curve(&[5, 0, 3, 4, 6]);
+        let plot = to_plot(&curve, &curve[1], 3, 3);
+
+        // Columns get positions 0, 1-2 and 3-4
+        assert_eq!(plot, concat!(
+            "5 |* *\n",
+            "  |\n",
+            "0 | o\n",
+            "  +---\n",
+            "    ^\n",
+            "Positions 0 to 4, cheapest: 0 at position 1\n",
+        ));
+    }
+
+    #[test]
+    fn optimum_column_matches_its_slice(){
+        for len in 1..30 {
+            let curve = curve(&vec![1; len]);
+
+            for width in 1..=len {
+                for idx in 0..len {
+                    let plot = to_plot(&curve, &curve[idx], width, 2);
+                    let marker = plot.lines().nth(3).unwrap().find('^').unwrap() - 3;
+                    let column_start = marker * len / width;
+                    let column_end = (marker + 1) * len / width;
+                    assert!((column_start..column_end).contains(&idx), "{} positions in {} columns, position {}", len, width, idx);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn flat_plot(){
+        let curve = curve(&[2, 2]);
+        let plot = to_plot(&curve, &curve[0], 10, 2);
+        assert!(plot.starts_with("2 |\n2 |o*\n"));
+    }
+}
@@ -0,0 +1,133 @@
+use std::str::FromStr;
+
+// How much fuel a crab burns to move some distance
+pub trait FuelModel {
+    fn cost(&self, distance: u32) -> u64;
+
+    // Whether each step costs at least as much as the previous one. The total cost of the crabs then
+    // only goes down then up when going through the positions, so the cheapest one can be searched for.
+    fn is_convex(&self) -> bool;
+}
+
+// Every step costs one fuel, the model of part 1
+pub struct Linear;
+
+// Each step costs one more than the previous one, the model of part 2
+pub struct Triangular;
+
+// Moving n steps costs n * n fuel
+pub struct Quadratic;
+
+// Cost of each step, the last one being repeated for steps past the end of the list
+pub struct Steps(Vec<u32>);
+
+#[derive(Debug, PartialEq)]
+pub enum ModelError {
+    UnknownModel(String),
+    InvalidStepCost(String),
+    NoStepCost,
+}
+
+impl FuelModel for Linear {
+    fn cost(&self, distance: u32) -> u64 {
+        distance as u64
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+impl FuelModel for Triangular {
+    fn cost(&self, distance: u32) -> u64 {
+        let distance = distance as u64;
+        distance * (distance + 1) / 2
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+impl FuelModel for Quadratic {
+    fn cost(&self, distance: u32) -> u64 {
+        distance as u64 * distance as u64
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+impl FuelModel for Steps {
+    fn cost(&self, distance: u32) -> u64 {
+        let distance = distance as usize;
+        let listed = distance.min(self.0.len());
+        let last = *self.0.last().unwrap();
+
+        self.0[..listed].iter().map(|step| *step as u64).sum::<u64>() + (distance - listed) as u64 * last as u64
+    }
+
+    fn is_convex(&self) -> bool {
+        self.0.windows(2).all(|steps| steps[0] <= steps[1])
+    }
+}
+
+// Accepted forms: `linear`, `triangular`, `quadratic` and `steps:<costs>` with the cost of each step
+// separated by commas, like `steps:1,1,2,3`
+pub fn parse_model(s: &str) -> Result<Box<dyn FuelModel>, ModelError> {
+    Ok(match s {
+        "linear" => Box::new(Linear),
+        "triangular" => Box::new(Triangular),
+        "quadratic" => Box::new(Quadratic),
+        _ => match s.strip_prefix("steps:") {
+            Some(steps) => {
+                let steps = steps
+                    .split(',')
+                    .filter(|step| !step.is_empty())
+                    .map(|step| u32::from_str(step).map_err(|_| ModelError::InvalidStepCost(step.to_string())))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                if steps.is_empty() {
+                    return Err(ModelError::NoStepCost);
+                }
+
+                Box::new(Steps(steps))
+            },
+            None => return Err(ModelError::UnknownModel(s.to_string())),
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_model, ModelError};
+
+    macro_rules! model_test {
+        ($name:ident, $model:literal, convex: $convex:literal, [$($cost:literal),*]) => {
+            #[test]
+            fn $name(){
+                let model = parse_model($model).expect("Model should parse");
+                assert_eq!(model.is_convex(), $convex);
+
+                let costs = [$($cost),*];
+                for (distance, cost) in costs.iter().enumerate() {
+                    assert_eq!(model.cost(distance as u32), *cost, "Cost of {} steps", distance);
+                }
+            }
+        };
+    }
+
+    model_test!(linear,             "linear", convex: true, [0, 1, 2, 3, 4]);
+    model_test!(triangular,         "triangular", convex: true, [0, 1, 3, 6, 10]);
+    model_test!(quadratic,          "quadratic", convex: true, [0, 1, 4, 9, 16]);
+    model_test!(increasing_steps,   "steps:1,2,2,5", convex: true, [0, 1, 3, 5, 10, 15, 20]);
+    model_test!(decreasing_steps,   "steps:5,1", convex: false, [0, 5, 6, 7]);
+
+    #[test]
+    fn invalid_models(){
+        assert_eq!(parse_model("cubic").err(), Some(ModelError::UnknownModel("cubic".to_string())));
+        assert_eq!(parse_model("steps:1,x").err(), Some(ModelError::InvalidStepCost("x".to_string())));
+        assert_eq!(parse_model("steps:").err(), Some(ModelError::NoStepCost));
+    }
+}
@@ -0,0 +1,220 @@
+use crate::Crab;
+
+// Crabs meeting at the same position, which are all the crabs between two positions
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group {
+    pub position: u32,
+    // Positions of the crabs the furthest away on each side
+    pub first_crab_position: u32,
+    pub last_crab_position: u32,
+    // Total weight of the crabs in the group
+    pub weight: u64,
+    pub fuel: u128,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grouping {
+    pub groups: Vec<Group>,
+    // Index of the group of each crab, in the order of the input
+    pub assignments: Vec<usize>,
+    pub fuel: u128,
+}
+
+// Crabs sorted by position, the ones with the same position merged together
+pub struct Line {
+    positions: Vec<u64>,
+    // Prefix sums: weights[i] is the weight of the crabs before positions[i]
+    weights: Vec<u64>,
+    // Same with the weight times the position, which doesn't always fit in 64 bits
+    moments: Vec<u128>,
+}
+
+impl Line {
+    pub fn new(crabs: &[Crab]) -> Self {
+        let mut sorted_crabs = crabs.to_vec();
+        sorted_crabs.sort_unstable_by_key(|crab| crab.position);
+
+        let mut positions = Vec::new();
+        let mut weights = vec![0];
+        let mut moments = vec![0];
+
+        for crab in sorted_crabs {
+            let position = crab.position as u64;
+            let weight = crab.weight as u64;
+
+            if positions.last() == Some(&position) {
+                *weights.last_mut().unwrap() += weight;
+                *moments.last_mut().unwrap() += weight as u128 * position as u128;
+            } else {
+                positions.push(position);
+                weights.push(weights.last().unwrap() + weight);
+                moments.push(moments.last().unwrap() + weight as u128 * position as u128);
+            }
+        }
+
+        Self { positions, weights, moments }
+    }
+
+    // Number of different positions
+    pub fn position_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    // Best position for the crabs from positions[first] to positions[last] included, and the fuel they
+    // need to get there. That's the weighted median: at least half of the weight on each side.
+    pub fn alignment(&self, first: usize, last: usize) -> (u32, u128) {
+        let weight = |from: usize, to: usize| self.weights[to] - self.weights[from];
+        let moment = |from: usize, to: usize| self.moments[to] - self.moments[from];
+
+        let half = weight(first, last + 1).div_ceil(2);
+        let (mut median, mut high) = (first, last);
+        while median < high {
+            let middle = median + (high - median) / 2;
+
+            if weight(first, middle + 1) < half {
+                median = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+
+        let position = self.positions[median];
+        let weighted_position = |from: usize, to: usize| position as u128 * weight(from, to) as u128;
+
+        let fuel_from_left = weighted_position(first, median + 1) - moment(first, median + 1);
+        let fuel_from_right = moment(median + 1, last + 1) - weighted_position(median + 1, last + 1);
+
+        (position as u32, fuel_from_left + fuel_from_right)
+    }
+}
+
+// Splits the crabs into `group_count` groups meeting at different positions, burning as little fuel as
+// possible with the fuel model of part 1. There is always a best split where the groups don't overlap,
+// so the groups are found by dynamic programming on where each of them starts: the cheapest way of
+// aligning the first crabs in some groups only depends on where the last of these groups starts.
+pub fn align_in_groups(crabs: &[Crab], group_count: usize) -> Grouping {
+    assert!(group_count > 0 && !crabs.is_empty(), "Need at least one group and one crab");
+
+    let line = Line::new(crabs);
+    let position_count = line.position_count();
+    // More groups than positions would be empty
+    let group_count = group_count.min(position_count);
+
+    // fuel[groups][end]: cheapest fuel to align the crabs before positions[end] in that many groups,
+    // along with where the last group starts
+    let mut fuel = vec![vec![(u128::MAX, 0); position_count + 1]; group_count + 1];
+    fuel[0][0] = (0, 0);
+
+    for groups in 1..=group_count {
+        for end in groups..=position_count {
+            fuel[groups][end] = (groups - 1..end)
+                .filter(|start| fuel[groups - 1][*start].0 != u128::MAX)
+                .map(|start| (fuel[groups - 1][start].0 + line.alignment(start, end - 1).1, start))
+                .min()
+                .unwrap();
+        }
+    }
+
+    let mut groups = Vec::with_capacity(group_count);
+    let mut end = position_count;
+    for remaining in (1..=group_count).rev() {
+        let start = fuel[remaining][end].1;
+        let (position, group_fuel) = line.alignment(start, end - 1);
+
+        groups.push(Group {
+            position,
+            first_crab_position: line.positions[start] as u32,
+            last_crab_position: line.positions[end - 1] as u32,
+            weight: line.weights[end] - line.weights[start],
+            fuel: group_fuel,
+        });
+
+        end = start;
+    }
+    groups.reverse();
+
+    let assignments = crabs
+        .iter()
+        .map(|crab| groups.iter().position(|group| crab.position <= group.last_crab_position).unwrap())
+        .collect();
+
+    Grouping {
+        groups,
+        assignments,
+        fuel: fuel[group_count][position_count].0,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::align_in_groups;
+    use crate::{pseudo_random, Crab};
+
+    fn crabs(positions: &[u32]) -> Vec<Crab> {
+        positions.iter().map(|position| Crab { position: *position, weight: 1 }).collect()
+    }
+
+    macro_rules! grouping_test {
+        ($name:ident, $crabs:expr, $group_count:literal -> [$($position:literal),*], $fuel:literal) => {
+            #[test]
+            fn $name(){
+                let grouping = align_in_groups(&$crabs, $group_count);
+                let positions = grouping.groups.iter().map(|group| group.position).collect::<Vec<_>>();
+                assert_eq!(positions, vec![$($position),*]);
+                assert_eq!(grouping.fuel, $fuel);
+                assert_eq!(grouping.fuel, grouping.groups.iter().map(|group| group.fuel).sum::<u128>());
+            }
+        };
+    }
+
+    grouping_test!(example_one_group,       crabs(&[16, 1, 2, 0, 4, 2, 7, 1, 2, 14]), 1 -> [2], 37);
+    grouping_test!(example_two_groups,      crabs(&[16, 1, 2, 0, 4, 2, 7, 1, 2, 14]), 2 -> [2, 14], 13);
+    grouping_test!(two_clusters,            crabs(&[1, 2, 3, 10, 11, 12]), 2 -> [2, 11], 4);
+    grouping_test!(one_group_per_position,  crabs(&[5, 1, 5, 9]), 3 -> [1, 5, 9], 0);
+    grouping_test!(more_groups_than_crabs,  crabs(&[3, 7]), 5 -> [3, 7], 0);
+    grouping_test!(heavy_crab,              [Crab { position: 0, weight: 5 }, Crab { position: 10, weight: 1 }], 1 -> [0], 10);
+
+    #[test]
+    fn heavy_crabs(){
+        let heavy_crabs = [
+            Crab { position: 0, weight: 4_000_000_000 },
+            Crab { position: 4_000_000_000, weight: 4_000_000_000 },
+            Crab { position: 5, weight: 1 },
+        ];
+
+        assert_eq!(align_in_groups(&heavy_crabs, 1).fuel, 16_000_000_000_000_000_000);
+        assert_eq!(align_in_groups(&heavy_crabs, 2).fuel, 5);
+    }
+
+    #[test]
+    fn assignments_follow_the_input(){
+        let grouping = align_in_groups(&crabs(&[11, 1, 12, 2, 3, 10]), 2);
+        assert_eq!(grouping.assignments, vec![1, 0, 1, 0, 0, 1]);
+        assert_eq!(grouping.groups[0].first_crab_position, 1);
+        assert_eq!(grouping.groups[0].last_crab_position, 3);
+        assert_eq!(grouping.groups[1].weight, 3);
+    }
+
+    #[test]
+    fn matches_every_split_in_two(){
+        let mut next = pseudo_random(5, 40);
+
+        for len in 2..20 {
+            let mut positions = (0..len).map(|_| next()).collect::<Vec<_>>();
+            positions.sort_unstable();
+            positions.dedup();
+            if positions.len() < 2 {
+                continue;
+            }
+
+            // Fuel of the crabs all meeting at the best position
+            let single = |crabs: &[u32]| (0..40).map(|target| crabs.iter().map(|p| p.abs_diff(target) as u128).sum::<u128>()).min().unwrap();
+            let best_split = (1..positions.len())
+                .map(|split| single(&positions[..split]) + single(&positions[split..]))
+                .min()
+                .unwrap();
+
+            assert_eq!(align_in_groups(&crabs(&positions), 2).fuel, best_split);
+        }
+    }
+}
@@ -1,47 +1,352 @@
-use std::cmp::min;
 use std::env::args;
 use std::str::FromStr;
 
+use fuel_model::{parse_model, FuelModel, Linear};
+
+mod curve;
+mod fuel_model;
+mod groups;
+
+const PLOT_WIDTH: usize = 80;
+const PLOT_HEIGHT: usize = 20;
+
+const USAGE: &str = "USAGE: day7 <input file> [--model linear|triangular|quadratic|steps:<cost>,<cost>...] [--curve csv|plot|--groups <count>]";
+
 fn main() {
-    let filename = args().nth(1).expect("USAGE: day7 <input file>");
-    let content = std::fs::read_to_string(filename).unwrap();
+    let arguments = args().skip(1).collect::<Vec<_>>();
+
+    let mut filename = None;
+    let mut model = None;
+    let mut curve_format = None;
+    let mut group_count = None;
+
+    let mut arguments_iter = arguments.iter();
+    while let Some(argument) = arguments_iter.next() {
+        if argument == "--model" {
+            let name = arguments_iter.next().expect(USAGE);
+            model = Some(parse_model(name).unwrap_or_else(|e| panic!("Invalid model {}: {:?}", name, e)));
+        } else if argument == "--curve" {
+            curve_format = Some(arguments_iter.next().filter(|f| *f == "csv" || *f == "plot").expect(USAGE));
+        } else if argument == "--groups" {
+            group_count = Some(arguments_iter.next().and_then(|n| usize::from_str(n).ok()).filter(|n| *n > 0).expect(USAGE));
+        } else {
+            filename = Some(argument);
+        }
+    }
+
+    let content = std::fs::read_to_string(filename.expect(USAGE)).unwrap();
+
+    let crabs = parse_crabs(&content).unwrap_or_else(|e| panic!("Invalid input: {:?}", e));
+
+    if let Some(group_count) = group_count {
+        assert!(model.is_none(), "Groups only work with the fuel model of part 1");
+        print_groups(&groups::align_in_groups(&crabs, group_count));
+        return;
+    }
+
+    if let Some(curve_format) = curve_format {
+        // Part 1 if no model is given
+        let model = model.unwrap_or_else(|| Box::new(Linear));
+        let curve = fuel_curve(&crabs, |diff| model.cost(diff));
+
+        if curve_format == "csv" {
+            print!("{}", curve::to_csv(&curve));
+        } else {
+            let optimum = calculate_fuel_requirements(&crabs, |diff| model.cost(diff));
+            print!("{}", curve::to_plot(&curve, &optimum, PLOT_WIDTH, PLOT_HEIGHT));
+        }
+        return;
+    }
+
+    if let Some(model) = model {
+        let alignment = cheapest_fuel_requirement(&crabs, model.as_ref());
+        println!("Cheapest fuel requirement: {} at position {}", alignment.fuel, alignment.position);
+        return;
+    }
+
+    let fuel_req_part1 = linear_fuel_requirements(&crabs);
+    println!("Cheapest fuel requirement for part 1: {} at position {}", fuel_req_part1.fuel, fuel_req_part1.position);
+
+    let fuel_req_part2 = triangular_fuel_requirements(&crabs);
+    println!("Cheapest fuel requirement for part 2: {} at position {}", fuel_req_part2.fuel, fuel_req_part2.position);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Crab {
+    position: u32,
+    // How many times the fuel of a crab of weight 1 it burns for the same move
+    weight: u32,
+}
 
-    let crab_positions = content
+#[derive(Debug, PartialEq)]
+enum ParseError {
+    InvalidCrab(String),
+    // Crabs of weight 0 don't burn any fuel, but there has to be at least one crab that does
+    NoWeight,
+}
+
+// Crabs separated by commas, each being a position optionally followed by a weight: `16:2,1,2` has
+// a crab at 16 with a weight of 2, the other crabs having a weight of 1
+fn parse_crabs(content: &str) -> Result<Vec<Crab>, ParseError> {
+    let crabs = content
+        .trim()
         .split(',')
-        .map(|pos| u32::from_str(pos).unwrap())
-        .collect::<Vec<_>>();
+        .map(|crab| {
+            let (position, weight) = crab.split_once(':').unwrap_or((crab, "1"));
+
+            match (u32::from_str(position), u32::from_str(weight)) {
+                (Ok(position), Ok(weight)) => Ok(Crab { position, weight }),
+                _ => Err(ParseError::InvalidCrab(crab.to_string())),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if crabs.iter().all(|crab| crab.weight == 0) {
+        return Err(ParseError::NoWeight);
+    }
+
+    Ok(crabs)
+}
+
+fn print_groups(grouping: &groups::Grouping) {
+    for (idx, group) in grouping.groups.iter().enumerate() {
+        println!(
+            "Group {}: crabs from {} to {} weighing {} meet at position {} burning {} fuel",
+            idx, group.first_crab_position, group.last_crab_position, group.weight, group.position, group.fuel
+        );
+    }
+
+    let assignments = grouping.assignments.iter().map(ToString::to_string).collect::<Vec<_>>();
+    println!("Group of each crab: {}", assignments.join(","));
+    println!("Total fuel: {}", grouping.fuel);
+}
+
+// Where the crabs meet and how much fuel they burn to get there
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Alignment {
+    position: u32,
+    // Heavy crabs can burn more than 64 bits of fuel
+    fuel: u128,
+}
+
+// 1 + 2 + ... + n
+fn triangular(n: u32) -> u64 {
+    let n = n as u64;
+    n * (n + 1) / 2
+}
+
+// A crab of weight n burns as much fuel as n crabs at the same position
+fn fuel_requirement<F>(crabs: &[Crab], position: u32, fuel_calculation_fun: F) -> u128
+    where F: Fn(u32) -> u64
+{
+    crabs
+        .iter()
+        .map(|crab| crab.weight as u128 * fuel_calculation_fun(crab.position.abs_diff(position)) as u128)
+        .sum()
+}
+
+// When each step costs the same, the weighted median is the best position: moving away from it brings
+// closer at most as much weight as it moves away
+fn linear_fuel_requirements(crabs: &[Crab]) -> Alignment {
+    let line = groups::Line::new(crabs);
+    let (position, fuel) = line.alignment(0, line.position_count() - 1);
+
+    Alignment { position, fuel }
+}
 
-    let fuel_req_part1 = calculate_fuel_requirements(&crab_positions, |diff| diff);
-    println!("Cheapest fuel requirement for part 1: {}", fuel_req_part1);
+// When each step costs one more than the previous one, the best position is less than half a step
+// away from the weighted mean, so it is either the mean rounded down or rounded up
+fn triangular_fuel_requirements(crabs: &[Crab]) -> Alignment {
+    let weight = crabs.iter().map(|crab| crab.weight as u128).sum::<u128>();
+    let moment = crabs.iter().map(|crab| crab.weight as u128 * crab.position as u128).sum::<u128>();
+    let mean_floor = (moment / weight) as u32;
 
-    let fuel_req_part2 = calculate_fuel_requirements(&crab_positions, |diff| (1..=diff).sum());
-    println!("Cheapest fuel requirement for part 2: {}", fuel_req_part2);
+    [mean_floor, mean_floor + 1]
+        .iter()
+        .map(|position| Alignment {
+            position: *position,
+            fuel: fuel_requirement(crabs, *position, triangular),
+        })
+        .min_by_key(|alignment| alignment.fuel)
+        .unwrap()
 }
 
-fn calculate_fuel_requirements<F>(initial_positions: &[u32], fuel_calculation_fun: F) -> u32
-    where F: Fn(u32) -> u32
+// Searches for the cheapest position between the crabs, halving the range at each step when the model
+// allows it
+fn cheapest_fuel_requirement(crabs: &[Crab], model: &dyn FuelModel) -> Alignment {
+    if !model.is_convex() {
+        return calculate_fuel_requirements(crabs, |diff| model.cost(diff));
+    }
+
+    let cost_at = |position| fuel_requirement(crabs, position, |diff| model.cost(diff));
+    let mut low = crabs.iter().map(|crab| crab.position).min().unwrap();
+    let mut high = crabs.iter().map(|crab| crab.position).max().unwrap();
+
+    // The total cost only goes down then up: the cheapest position is the first one that is not more
+    // expensive than the next
+    while low < high {
+        let middle = low + (high - low) / 2;
+
+        if cost_at(middle) <= cost_at(middle + 1) {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+
+    Alignment { position: low, fuel: cost_at(low) }
+}
+
+// Fuel requirement of every position from 0 to the furthest crab
+fn fuel_curve<F>(crabs: &[Crab], fuel_calculation_fun: F) -> Vec<Alignment>
+    where F: Fn(u32) -> u64
 {
-    let largest_initial_position = *initial_positions.iter().max().unwrap();
-    let mut minimal_fuel_requirement = u32::MAX;
+    let largest_initial_position = crabs.iter().map(|crab| crab.position).max().unwrap();
 
-    for possible_position in 0..=largest_initial_position {
-        let fuel_requirement = initial_positions
-            .iter()
-            .map(|crab_position|{
-                let crab_position = *crab_position;
+    (0..=largest_initial_position)
+        .map(|possible_position| Alignment {
+            position: possible_position,
+            fuel: fuel_requirement(crabs, possible_position, &fuel_calculation_fun),
+        })
+        .collect()
+}
 
-                let difference_in_position = if crab_position > possible_position {
-                    crab_position - possible_position
-                } else {
-                    possible_position - crab_position
-                };
+// Tries every position, for models that don't allow anything smarter. Ties go to the smallest position.
+fn calculate_fuel_requirements<F>(crabs: &[Crab], fuel_calculation_fun: F) -> Alignment
+    where F: Fn(u32) -> u64
+{
+    fuel_curve(crabs, fuel_calculation_fun)
+        .into_iter()
+        .min_by_key(|alignment| (alignment.fuel, alignment.position))
+        .unwrap()
+}
+
+// Small pseudo random numbers below `max` for the tests, the same each time for a given seed
+#[cfg(test)]
+fn pseudo_random(seed: u32, max: u32) -> impl FnMut() -> u32 {
+    let mut state = seed;
+
+    move || {
+        state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        (state >> 16) % max
+    }
+}
 
-                fuel_calculation_fun(difference_in_position)
-            })
-            .sum::<u32>();
+#[cfg(test)]
+mod test {
+    use std::fs;
 
-        minimal_fuel_requirement = min(minimal_fuel_requirement, fuel_requirement);
+    use super::{calculate_fuel_requirements, parse_crabs, Alignment, Crab, ParseError, cheapest_fuel_requirement, linear_fuel_requirements, pseudo_random, triangular, triangular_fuel_requirements};
+    use crate::fuel_model::parse_model;
+
+    fn get_crabs(filename: &str) -> Vec<Crab> {
+        parse_crabs(&fs::read_to_string(filename).unwrap()).unwrap()
+    }
+
+    fn crabs(positions: &[u32]) -> Vec<Crab> {
+        positions.iter().map(|position| Crab { position: *position, weight: 1 }).collect()
     }
 
-    minimal_fuel_requirement
+    macro_rules! solution_test {
+        ($name:ident, ($part1:literal at $position1:literal, $part2:literal at $position2:literal), $crabs:expr) => {
+            #[test]
+            fn $name(){
+                let crabs = $crabs;
+                assert_eq!(linear_fuel_requirements(&crabs), Alignment { position: $position1, fuel: $part1 });
+                assert_eq!(triangular_fuel_requirements(&crabs), Alignment { position: $position2, fuel: $part2 });
+
+                // Other positions may cost the same
+                assert_eq!(calculate_fuel_requirements(&crabs, |diff| diff as u64).fuel, $part1);
+                assert_eq!(calculate_fuel_requirements(&crabs, triangular).fuel, $part2);
+
+                assert_eq!(cheapest_fuel_requirement(&crabs, parse_model("linear").unwrap().as_ref()).fuel, $part1);
+                assert_eq!(cheapest_fuel_requirement(&crabs, parse_model("triangular").unwrap().as_ref()).fuel, $part2);
+            }
+        };
+    }
+
+    solution_test!(example,             (37 at 2, 168 at 5), get_crabs("../files/day7/example.txt"));
+    solution_test!(input,               (345197 at 340, 96361606 at 475), get_crabs("../files/day7/input.txt"));
+    solution_test!(single_crab,         (0 at 5, 0 at 5), crabs(&[5]));
+    solution_test!(two_crabs,           (3 at 0, 4 at 1), crabs(&[0, 3]));
+    solution_test!(mean_rounded_up,     (10 at 10, 40 at 7), crabs(&[0, 10, 10]));
+    // The triangular fuel doesn't fit in 32 bits anymore
+    solution_test!(far_apart,           (300000 at 300000, 30000200000 at 200000), crabs(&[0, 300000, 300000]));
+    solution_test!(weightless_crab,     (0 at 10, 0 at 10), parse_crabs("0:0,10").unwrap());
+    // As many crabs at 1 would need gigabytes
+    solution_test!(heavy_crab,          (4 at 1, 10 at 1), parse_crabs("1:3000000000,5").unwrap());
+
+    #[test]
+    fn fuel_past_64_bits(){
+        let crabs = parse_crabs("0:4000000000,4000000000:4000000000,5").unwrap();
+        let part1 = Alignment { position: 5, fuel: 16000000000000000000 };
+        let part2 = Alignment { position: 2000000000, fuel: 16000000009999999991000000010 };
+
+        // Too many positions to try them all
+        assert_eq!(linear_fuel_requirements(&crabs), part1);
+        assert_eq!(triangular_fuel_requirements(&crabs), part2);
+        assert_eq!(cheapest_fuel_requirement(&crabs, parse_model("linear").unwrap().as_ref()).fuel, part1.fuel);
+        assert_eq!(cheapest_fuel_requirement(&crabs, parse_model("triangular").unwrap().as_ref()), part2);
+    }
+
+    #[test]
+    fn crabs_with_weights(){
+        assert_eq!(parse_crabs("16:2,1,2:0\n"), Ok(vec![
+            Crab { position: 16, weight: 2 },
+            Crab { position: 1, weight: 1 },
+            Crab { position: 2, weight: 0 },
+        ]));
+    }
+
+    #[test]
+    fn invalid_crabs(){
+        assert_eq!(parse_crabs("1,x:2"), Err(ParseError::InvalidCrab("x:2".to_string())));
+        assert_eq!(parse_crabs("1,2:"), Err(ParseError::InvalidCrab("2:".to_string())));
+        assert_eq!(parse_crabs("1:0,2:0"), Err(ParseError::NoWeight));
+    }
+
+    #[test]
+    fn weights_are_repeated_crabs(){
+        let weighted = parse_crabs("16:2,1,2:3,0,7:0,14").unwrap();
+        let repeated = crabs(&[16, 16, 1, 2, 2, 2, 0, 14]);
+
+        assert_eq!(linear_fuel_requirements(&weighted).fuel, linear_fuel_requirements(&repeated).fuel);
+        assert_eq!(triangular_fuel_requirements(&weighted), triangular_fuel_requirements(&repeated));
+        assert_eq!(calculate_fuel_requirements(&weighted, triangular), calculate_fuel_requirements(&repeated, triangular));
+    }
+
+    #[test]
+    fn triangular_numbers(){
+        for n in 0..50 {
+            assert_eq!(triangular(n), (1..=n as u64).sum());
+        }
+    }
+
+    #[test]
+    fn search_on_every_model(){
+        let crabs = get_crabs("../files/day7/example.txt");
+
+        for name in ["linear", "triangular", "quadratic", "steps:1,3,3,7", "steps:4,1,1,6"] {
+            let model = parse_model(name).unwrap();
+            assert_eq!(
+                cheapest_fuel_requirement(&crabs, model.as_ref()).fuel,
+                calculate_fuel_requirements(&crabs, |diff| model.cost(diff)).fuel,
+                "Model {}", name
+            );
+        }
+    }
+
+    #[test]
+    fn matches_brute_force_on_many_inputs(){
+        let mut next = pseudo_random(17, 50);
+
+        for len in 1..30 {
+            // Some crabs are weightless, but never all of them
+            let crabs = (0..len)
+                .map(|idx| Crab { position: next(), weight: if idx == 0 { 1 } else { next() % 4 } })
+                .collect::<Vec<_>>();
+            assert_eq!(linear_fuel_requirements(&crabs).fuel, calculate_fuel_requirements(&crabs, |diff| diff as u64).fuel);
+            assert_eq!(triangular_fuel_requirements(&crabs).fuel, calculate_fuel_requirements(&crabs, triangular).fuel);
+        }
+    }
 }
@@ -29,12 +29,8 @@ fn part1(notes: &[Vec<String>]) {
 
     let numbers_count = outputs
         .iter()
-        .map(|out_line| out_line.split(" "))
-        .flatten()
-        .filter(|output_segments| match output_segments.len() {
-            2 | 3 | 4 | 7 => true,
-            _ => false
-        })
+        .flat_map(|out_line| out_line.split(' '))
+        .filter(|output_segments| matches!(output_segments.len(), 2 | 3 | 4 | 7))
         .count();
 
     println!("Count of numbers with 2, 3, 4 and 7 digits: {:?}", numbers_count);
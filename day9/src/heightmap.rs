@@ -11,7 +11,7 @@ impl HeightMap {
     pub fn new(map: &str) -> Self {
         let lines = map.lines().map(str::to_string).collect::<Vec<String>>();
         let lines_count = lines.len();
-        let columns_count = lines.get(0).map(|col| col.len()).unwrap_or(0);
+        let columns_count = lines.first().map(|col| col.len()).unwrap_or(0);
 
         if lines_count == 0 || columns_count == 0 {
             // Too lazy to do proper error handling
@@ -20,8 +20,7 @@ impl HeightMap {
 
         let individual_digits = lines
             .iter()
-            .map(|line| line.split(""))
-            .flatten()
+            .flat_map(|line| line.split(""))
             .filter(|digit| !digit.is_empty())
             .map(u16::from_str)
             .map(Result::unwrap)